@@ -1,4 +1,5 @@
 import express from "express";
+import { readFileSync } from "fs";
 
 /// Mock Data
 interface HistoryEntry {
@@ -72,6 +73,17 @@ const data = {
 
 const app = express();
 
+app.get("/api/bot/user/:userId/history", (req, res) => {
+  const { userId } = req.params;
+  if (userId !== "WDX") {
+    res.statusCode = 404;
+    res.json({});
+  } else {
+    res.statusCode = 200;
+    res.json({ cmdrName: data.cmdrName, history: data.recentHistory });
+  }
+});
+
 app.get("/api/bot/user/:userId", (req, res) => {
   console.log(req.headers);
   const { userId } = req.params;
@@ -84,6 +96,27 @@ app.get("/api/bot/user/:userId", (req, res) => {
   }
 });
 
+// Pretends that Discord User 1234 uploaded a Journal of CMDR WDX
+app.get("/api/bot/link/verify", (req, res) => {
+  console.log(req.query);
+  const verified =
+    req.query.discord_id === "1234" &&
+    String(req.query.cmdr).toLowerCase() === "wdx";
+  res.statusCode = 200;
+  res.json({ verified, cmdrName: verified ? "WDX" : null });
+});
+
+app.get("/killboard/get/kills", (req, res) => {
+  console.log(req.query);
+  // Run the mock server from within mock_server/ so the file is found
+  res.statusCode = 200;
+  res.json(JSON.parse(readFileSync("killsummary.json", "utf8")));
+});
+
+// The Bot's webhook (WEBHOOK_LISTEN_ADDRESS) can be fed with the sample kills in webhook_kills.json:
+//   curl -X POST http://localhost:8080/webhook/kills -H "Authorization: Bearer $WEBHOOK_SECRET" \
+//     -H "Content-Type: application/json" --data @webhook_kills.json
+
 app.listen(80, () => {
   console.log("Listening on :80");
 });
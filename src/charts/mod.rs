@@ -0,0 +1,233 @@
+use std::{fmt::Display, io::Cursor};
+
+use image::{ImageOutputFormat, RgbImage};
+use once_cell::sync::OnceCell;
+use plotters::{prelude::*, coord::ranged1d::SegmentValue, style::register_font};
+
+use crate::{data::Environment, util::text::truncate};
+
+/// Width and height of every chart in pixels.
+const CHART_SIZE: (u32, u32) = (800, 450);
+
+/// Font family the font from [Environment::chart_font_path] is registered as.
+const FONT_FAMILY: &str = "sans-serif";
+
+/// Longest name written next to a bar. Longer ones are cut off.
+const BAR_LABEL_LIMIT: usize = 20;
+
+/// Most labels on the x axis of a line chart. Leaves enough room for dates as `YYYY-MM-DD`, plotters picks evenly spaced ones.
+const MAX_X_LABELS: usize = 8;
+
+const BAR_COLOR: RGBColor = RGBColor(88, 101, 242);
+
+/// The font can only be registered once per process, so the outcome of the first attempt is kept.
+static FONT: OnceCell<Result<(), String>> = OnceCell::new();
+
+#[derive(Debug)]
+pub enum ChartError {
+    Font(String),
+    Render(String),
+    Encode(image::ImageError)
+}
+
+impl Display for ChartError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChartError::Font(err) => write!(f, "Failed to load the chart font: {}", err),
+            ChartError::Render(err) => write!(f, "Failed to draw the chart: {}", err),
+            ChartError::Encode(err) => write!(f, "Failed to encode the chart as PNG: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ChartError {}
+
+impl<E: std::error::Error + Send + Sync> From<DrawingAreaErrorKind<E>> for ChartError {
+    fn from(err: DrawingAreaErrorKind<E>) -> Self {
+        return ChartError::Render(err.to_string());
+    }
+}
+
+/// One line of a line chart. Has a value for every label on the x axis.
+pub struct ChartLine<'a> {
+    pub label: &'a str,
+    pub color: RGBColor,
+    pub values: Vec<u32>
+}
+
+/// Registers the configured font with plotters, which draws no text at all without one.
+fn ensure_font() -> Result<(), ChartError> {
+    return register_font_from(Environment::chart_font_path);
+}
+
+/// Registers the font at the path `font_path` returns. Only the first call reads a font, later ones return its outcome.
+fn register_font_from(font_path: impl FnOnce() -> String) -> Result<(), ChartError> {
+    let result = FONT.get_or_init(|| {
+        let path = font_path();
+        let bytes = std::fs::read(&path).map_err(|err| format!("{}: {}", path, err))?;
+        // plotters keeps the font for the rest of the process
+        let bytes: &'static [u8] = Box::leak(bytes.into_boxed_slice());
+        return register_font(FONT_FAMILY, FontStyle::Normal, bytes).map_err(|_| format!("{} is not a valid TrueType font", path));
+    });
+    return result.clone().map_err(ChartError::Font);
+}
+
+fn encode_png(buffer: Vec<u8>) -> Result<Vec<u8>, ChartError> {
+    let image = RgbImage::from_raw(CHART_SIZE.0, CHART_SIZE.1, buffer).ok_or_else(|| ChartError::Render("Buffer does not match the chart size".to_string()))?;
+    let mut png = Cursor::new(Vec::new());
+    image.write_to(&mut png, ImageOutputFormat::Png).map_err(ChartError::Encode)?;
+    return Ok(png.into_inner());
+}
+
+/// Horizontal bars, the first one on top. Meant for leaderboards. Negative values are drawn to the left of zero.
+pub fn bar_chart(title: &str, bars: &[(String, f64)]) -> Result<Vec<u8>, ChartError> {
+    ensure_font()?;
+    let mut buffer = vec![0; (CHART_SIZE.0 * CHART_SIZE.1 * 3) as usize];
+    {
+        let root = BitMapBackend::with_buffer(&mut buffer, CHART_SIZE).into_drawing_area();
+        root.fill(&WHITE)?;
+
+        // A single row can not be drawn, so a lone bar gets an empty row below
+        let rows = bars.len().max(2);
+        let min = bars.iter().map(|x| x.1).fold(0.0, f64::min);
+        let max = bars.iter().map(|x| x.1).fold(0.0, f64::max);
+        // Some room next to the longest bars, and a usable axis if every value is zero
+        let padding = ((max - min) * 0.1).max(1.0);
+        let x_range = match min < 0.0 {
+            true => min - padding..max + padding,
+            false => 0.0..max + padding
+        };
+        // The first bar is drawn at the top, so the position of a bar is counted from the bottom
+        let position = |i: usize| rows - 1 - i;
+        let labels: Vec<String> = (0..rows).map(|row| bars.get(rows - 1 - row).map(|(name, _)| truncate(name, BAR_LABEL_LIMIT)).unwrap_or_default()).collect();
+
+        let mut chart = ChartBuilder::on(&root)
+            .caption(title, (FONT_FAMILY, 24))
+            .margin(16)
+            .x_label_area_size(32)
+            .y_label_area_size(180)
+            .build_cartesian_2d(x_range, (0..rows - 1).into_segmented())?;
+
+        chart.configure_mesh()
+            .disable_y_mesh()
+            .y_labels(rows)
+            .y_label_formatter(&|x| match x {
+                SegmentValue::CenterOf(i) => labels.get(*i).cloned().unwrap_or_default(),
+                _ => String::new()
+            })
+            .label_style((FONT_FAMILY, 14))
+            .draw()?;
+
+        chart.draw_series(bars.iter().enumerate().map(|(i, (_, value))| {
+            let mut bar = Rectangle::new([(0.0, SegmentValue::Exact(position(i))), (*value, SegmentValue::Exact(position(i) + 1))], BAR_COLOR.filled());
+            bar.set_margin(4, 4, 0, 0);
+            bar
+        }))?;
+
+        root.present()?;
+    }
+    return encode_png(buffer);
+}
+
+/// One line per series over the labels of the x axis, oldest first. Meant for trends.
+pub fn line_chart(title: &str, x_labels: &[String], series: &[ChartLine]) -> Result<Vec<u8>, ChartError> {
+    ensure_font()?;
+    let mut buffer = vec![0; (CHART_SIZE.0 * CHART_SIZE.1 * 3) as usize];
+    {
+        let root = BitMapBackend::with_buffer(&mut buffer, CHART_SIZE).into_drawing_area();
+        root.fill(&WHITE)?;
+
+        let count = x_labels.len();
+        let max = series.iter().flat_map(|x| x.values.iter()).copied().max().unwrap_or(0);
+
+        let mut chart = ChartBuilder::on(&root)
+            .caption(title, (FONT_FAMILY, 24))
+            .margin(16)
+            .margin_right(40)
+            .x_label_area_size(32)
+            .y_label_area_size(48)
+            .build_cartesian_2d(0..count.saturating_sub(1).max(1), 0..max + max / 10 + 1)?;
+
+        chart.configure_mesh()
+            .x_labels(count.clamp(1, MAX_X_LABELS))
+            .x_label_formatter(&|x| x_labels.get(*x).cloned().unwrap_or_default())
+            .label_style((FONT_FAMILY, 14))
+            .draw()?;
+
+        for line in series {
+            let color = line.color;
+            chart.draw_series(LineSeries::new(line.values.iter().enumerate().map(|(i, value)| (i, *value)), color.stroke_width(2)))?
+                .label(line.label)
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 16, y)], color.stroke_width(2)));
+            chart.draw_series(line.values.iter().enumerate().map(|(i, value)| Circle::new((i, *value), 3, color.filled())))?;
+        }
+
+        if series.len() > 1 {
+            chart.configure_series_labels()
+                .background_style(WHITE.mix(0.8))
+                .border_style(BLACK)
+                .label_font((FONT_FAMILY, 14))
+                .position(SeriesLabelPosition::UpperLeft)
+                .draw()?;
+        }
+
+        root.present()?;
+    }
+    return encode_png(buffer);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use plotters::style::RGBColor;
+
+    use super::{bar_chart, line_chart, register_font_from, ChartLine};
+
+    /// The default of `CHART_FONT_PATH`.
+    const TEST_FONT: &str = "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf";
+
+    /// Registers the font of `CHART_FONT_PATH`, or its default. `false` if the font is not installed, the chart tests are skipped then.
+    fn font_available() -> bool {
+        let path = match std::env::var("CHART_FONT_PATH") {
+            Ok(val) if !val.trim().is_empty() => val,
+            _ => TEST_FONT.to_string()
+        };
+        if !Path::new(&path).exists() {
+            println!("[WARN] Skipping the chart test, there is no font at {}. Set CHART_FONT_PATH to a TrueType font to run it.", path);
+            return false;
+        }
+        register_font_from(|| path).unwrap();
+        return true;
+    }
+
+    fn is_png(data: &[u8]) -> bool {
+        return data.starts_with(&[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n']);
+    }
+
+    #[test]
+    fn bar_charts() {
+        if !font_available() {
+            return;
+        }
+        let bars = |values: &[f64]| values.iter().enumerate().map(|(i, x)| (format!("{}. CMDR with a rather long name", i + 1), *x)).collect::<Vec<_>>();
+        assert!(is_png(&bar_chart("Empty", &[]).unwrap()));
+        assert!(is_png(&bar_chart("Single", &bars(&[3.0])).unwrap()));
+        assert!(is_png(&bar_chart("Zero", &bars(&[0.0, 0.0])).unwrap()));
+        assert!(is_png(&bar_chart("Net score", &bars(&[-2.0, -5.0, -11.0])).unwrap()));
+        assert!(is_png(&bar_chart("Net score", &bars(&[12.0, 0.0, -4.0])).unwrap()));
+    }
+
+    #[test]
+    fn line_charts() {
+        if !font_available() {
+            return;
+        }
+        let line = |values: Vec<u32>| ChartLine { label: "Kills", color: RGBColor(0, 0, 0), values };
+        assert!(is_png(&line_chart("Empty", &[], &[]).unwrap()));
+        assert!(is_png(&line_chart("Empty", &[], &[line(vec![])]).unwrap()));
+        assert!(is_png(&line_chart("Single", &["2023-12-14".to_string()], &[line(vec![4])]).unwrap()));
+        let labels: Vec<String> = (1..=12).map(|x| format!("2023-{:02}-01", x)).collect();
+        assert!(is_png(&line_chart("Year", &labels, &[line(vec![0; 12]), line((0..12).collect())]).unwrap()));
+    }
+}
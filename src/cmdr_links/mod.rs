@@ -0,0 +1,50 @@
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+use rusqlite::{Connection, OptionalExtension, params};
+
+use crate::util::cmdr_name::CmdrName;
+
+/// Verified links between Discord accounts and CMDRs, kept in their own SQLite file.
+/// Unlike the Kill Cache this can not be rebuilt from the Data Server, so it must not be deleted.
+/// A Discord account is linked to at most one CMDR, and a CMDR to at most one Discord account.
+/// Cheap to clone, all clones share the same connection.
+#[derive(Clone)]
+pub struct CmdrLinks {
+    connection: Arc<Mutex<Connection>>
+}
+
+impl CmdrLinks {
+
+    pub fn open(path: &str) -> Result<Self, rusqlite::Error> {
+        let connection = Connection::open(path)?;
+        connection.execute_batch("
+            CREATE TABLE IF NOT EXISTS links (
+                discord_id INTEGER PRIMARY KEY,
+                cmdr_name TEXT NOT NULL,
+                cmdr_key TEXT NOT NULL UNIQUE,
+                linked_at INTEGER NOT NULL
+            );
+        ")?;
+        return Ok(CmdrLinks { connection: Arc::new(Mutex::new(connection)) });
+    }
+
+    /// Links `discord_id` to `cmdr`. Replaces the previous CMDR of the account, and takes the CMDR away from any other account.
+    pub fn link(&self, discord_id: u64, cmdr: &CmdrName) -> Result<(), rusqlite::Error> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+        transaction.execute("DELETE FROM links WHERE cmdr_key = ?1 OR discord_id = ?2", params![cmdr.key(), discord_id as i64])?;
+        transaction.execute(
+            "INSERT INTO links (discord_id, cmdr_name, cmdr_key, linked_at) VALUES (?1, ?2, ?3, ?4)",
+            params![discord_id as i64, cmdr.display(), cmdr.key(), Utc::now().timestamp()]
+        )?;
+        return transaction.commit();
+    }
+
+    /// The CMDR linked to `discord_id`, if any.
+    pub fn get_cmdr(&self, discord_id: u64) -> Result<Option<CmdrName>, rusqlite::Error> {
+        let connection = self.connection.lock().unwrap();
+        let name: Option<String> = connection.query_row("SELECT cmdr_name FROM links WHERE discord_id = ?1", params![discord_id as i64], |row| row.get(0)).optional()?;
+        return Ok(name.map(|x| CmdrName::new(&x)));
+    }
+}
@@ -10,10 +10,8 @@ use crate::{Context, Error};
 #[poise::command(slash_command)]
 pub async fn pvpadmin_force_weekly_summary(
     ctx: Context<'_>,
-    #[description="optional; Use YYYY-MM-DD format. If left empty, the current day is used."] for_date: Option<String>
+    #[description="optional; Use YYYY-MM-DD format. Posts the leaderboard week that ended by this day. Defaults to the last one."] for_date: Option<String>
 ) -> Result<(), Error> {
-    let is_admin = is_user_admin(&ctx).await;
-
     let override_date: Option<NaiveDate> = match for_date {
         Some(e) => {
             match NaiveDate::parse_from_str(e.as_str(), "%Y-%m-%d").map_err(|x| x.to_string()) {
@@ -27,6 +25,9 @@ pub async fn pvpadmin_force_weekly_summary(
         None => None
     };
 
+    ctx.defer_ephemeral().await?;
+    let is_admin = is_user_admin(&ctx).await;
+
 
     let _ = match is_admin {
         false => {
@@ -37,7 +38,7 @@ pub async fn pvpadmin_force_weekly_summary(
             (request, true)
         },
         true => {
-            let response = subscribe_for_event(override_date).await;
+            let response = subscribe_for_event(ctx.data(), override_date).await;
             let embed_color =  match response {
                 Ok(_) => Color::DARK_GREEN,
                 Err(_) => Color::RED
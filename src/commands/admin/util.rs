@@ -10,7 +10,7 @@ pub async fn is_user_admin(ctx: &Context<'_>) -> bool {
 
     return match user.has_role(ctx.http(), required_guild_id, required_role_id).await {
         Ok(is_admin) => is_admin,
-        Err(err) => {println!("Failed to check if user {} has admin role. Reason: {}", user.id, err.to_string()); false},
+        Err(err) => {println!("Failed to check if user {} has admin role. Reason: {}", user.id, err); false},
     }
 
 }
\ No newline at end of file
@@ -0,0 +1,8 @@
+use crate::kill_cache::cmdr_index::MAX_AUTOCOMPLETE_CHOICES;
+
+use super::super::Context;
+
+/// Suggests CMDR names from the Kill Cache for any CMDR name option.
+pub async fn autocomplete_cmdr(ctx: Context<'_>, partial: &str) -> impl Iterator<Item = String> {
+    return ctx.data().kill_cache.search_cmdrs(partial, MAX_AUTOCOMPLETE_CHOICES).into_iter();
+}
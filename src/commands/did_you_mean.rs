@@ -0,0 +1,67 @@
+use std::future::Future;
+
+use poise::{serenity_prelude::{ButtonStyle, CreateComponents, CreateEmbed, InteractionResponseType}, ReplyHandle};
+
+use super::{super::{Context, Error}, interaction::{await_click, interaction_id_prefix, set_components}};
+
+/// How many "did you mean" buttons are offered. They have to fit into a single action row.
+pub const MAX_DID_YOU_MEAN: usize = 5;
+
+const SUGGESTION_ACTION: &str = "suggest-";
+
+/// Adds a button for each suggested CMDR name. Does nothing without suggestions.
+pub fn add_suggestion_buttons<'a>(components: &'a mut CreateComponents, id_prefix: &str, suggestions: &[String]) -> &'a mut CreateComponents {
+    if suggestions.is_empty() {
+        return components;
+    }
+    return components.create_action_row(|row| {
+        for (index, name) in suggestions.iter().take(MAX_DID_YOU_MEAN).enumerate() {
+            row.create_button(|button| button
+                .style(ButtonStyle::Secondary)
+                .label(format!("CMDR {}", name))
+                .custom_id(format!("{}-{}{}", id_prefix, SUGGESTION_ACTION, index))
+            );
+        }
+        row
+    });
+}
+
+/// The suggestion a click on one of the buttons of [add_suggestion_buttons] stands for.
+pub fn chosen_suggestion(action: &str, suggestions: &[String]) -> Option<String> {
+    let index = action.strip_prefix(SUGGESTION_ACTION)?.parse::<usize>().ok()?;
+    return suggestions.get(index).cloned();
+}
+
+/// Lets the author click the suggestion buttons of `reply` to re-run the lookup with the suggested name.
+/// `lookup` builds the new embed for a name, and the names to suggest next. Stops once there is nothing left to suggest,
+/// or nobody clicked for a while, and removes the buttons.
+pub async fn handle_suggestion_clicks<F, Fut>(ctx: Context<'_>, reply: ReplyHandle<'_>, mut suggestions: Vec<String>, lookup: F) -> Result<(), Error>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = (CreateEmbed, Vec<String>)>
+{
+    let id_prefix = interaction_id_prefix(&ctx);
+
+    while !suggestions.is_empty() {
+        let (interaction, action) = match await_click(ctx, &id_prefix).await {
+            Some(click) => click,
+            None => break
+        };
+        // Acknowledge right away, the lookup may take longer than Discord waits for a response
+        interaction.create_interaction_response(ctx, |r| r.kind(InteractionResponseType::DeferredUpdateMessage)).await?;
+
+        let chosen = match chosen_suggestion(&action, &suggestions) {
+            Some(chosen) => chosen,
+            None => continue
+        };
+
+        let (embed, next_suggestions) = lookup(chosen).await;
+        suggestions = next_suggestions;
+
+        let mut components = CreateComponents::default();
+        add_suggestion_buttons(&mut components, &id_prefix, &suggestions);
+        interaction.edit_original_interaction_response(ctx, |r| r.set_embed(embed).set_components(components)).await?;
+    }
+
+    return set_components(ctx, &reply, CreateComponents::default()).await;
+}
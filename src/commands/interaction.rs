@@ -0,0 +1,43 @@
+use std::{sync::Arc, time::Duration};
+
+use poise::{serenity_prelude::{AttachmentType, CollectComponentInteraction, CreateComponents, MessageComponentInteraction}, ReplyHandle};
+
+use super::super::{Context, Error};
+
+/// How long buttons stay clickable after the last click.
+const CLICK_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// The prefix of the button ids of a single command invocation, so clicks on other messages are ignored.
+pub fn interaction_id_prefix(ctx: &Context<'_>) -> String {
+    return format!("{}-{}", ctx.command().name, ctx.id());
+}
+
+/// Waits for the author to click a button with an id made of `id_prefix` and an action, see [interaction_id_prefix].
+/// Returns the interaction and the action, or `None` if nobody clicked in time.
+pub async fn await_click(ctx: Context<'_>, id_prefix: &str) -> Option<(Arc<MessageComponentInteraction>, String)> {
+    let id_prefix = format!("{}-", id_prefix);
+    let filter_prefix = id_prefix.clone();
+    let interaction = CollectComponentInteraction::new(ctx)
+        .author_id(ctx.author().id)
+        .channel_id(ctx.channel_id())
+        .timeout(CLICK_TIMEOUT)
+        .filter(move |x| x.data.custom_id.starts_with(&filter_prefix))
+        .await?;
+
+    let action = interaction.data.custom_id[id_prefix.len()..].to_string();
+    return Some((interaction, action));
+}
+
+/// Replaces the buttons of a reply. Editing the message itself only touches the components, the reply builder would also clear the embed.
+pub async fn set_components(ctx: Context<'_>, reply: &ReplyHandle<'_>, components: CreateComponents) -> Result<(), Error> {
+    let mut message = reply.message().await?.into_owned();
+    message.edit(ctx, |m| m.set_components(components)).await?;
+    return Ok(());
+}
+
+/// Attaches a file to a reply. Like [set_components], the message is edited directly to keep the embed.
+pub async fn add_attachment(ctx: Context<'_>, reply: &ReplyHandle<'_>, attachment: AttachmentType<'_>) -> Result<(), Error> {
+    let mut message = reply.message().await?.into_owned();
+    message.edit(ctx, |m| m.attachment(attachment)).await?;
+    return Ok(());
+}
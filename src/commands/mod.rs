@@ -2,4 +2,15 @@ pub mod pvpself;
 pub mod pvpwhois;
 pub mod pvpregister;
 pub mod pvpweekly;
-pub mod admin;
\ No newline at end of file
+pub mod pvpleaderboard;
+pub mod pvplink;
+pub mod pvpshipstats;
+pub mod pvpversus;
+pub mod pvphotspots;
+pub mod pvpsystem;
+pub mod pvpactivity;
+pub mod pvptrends;
+pub mod admin;
+pub mod autocomplete;
+pub mod did_you_mean;
+pub mod interaction;
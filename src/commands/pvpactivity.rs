@@ -0,0 +1,78 @@
+use poise::serenity_prelude::Color;
+
+use crate::{other::{activity::ActivityHeatmap, leaderboard_period::LeaderboardPeriod}, kill_cache::sync::get_kills_between, util::cmdr_name::CmdrName};
+
+use super::{super::{Context, Error}, autocomplete::autocomplete_cmdr, pvpleaderboard::time_range_or_reply};
+
+/// Shows when kills happen by weekday and hour, for everyone or a single CMDR
+#[poise::command(slash_command)]
+pub async fn pvpactivity(
+    ctx: Context<'_>,
+    #[description="optional; Only count the kills and deaths of this CMDR"]
+    #[autocomplete = "autocomplete_cmdr"] cmdr: Option<String>,
+    #[description="optional; The period to look at. Defaults to the current month, or custom if from is set"] period: Option<LeaderboardPeriod>,
+    #[description="optional; Start of a custom period. YYYY-MM-DD, optionally followed by HH:MM (UTC)"] from: Option<String>,
+    #[description="optional; End of a custom period, inclusive. YYYY-MM-DD, optionally followed by HH:MM (UTC). Defaults to now"] to: Option<String>
+) -> Result<(), Error> {
+    let period = period.unwrap_or(match from {
+        Some(_) => LeaderboardPeriod::Custom,
+        None => LeaderboardPeriod::Month
+    });
+
+    let (start, end) = match time_range_or_reply(ctx, period, from, to).await? {
+        Some(range) => range,
+        None => return Ok(())
+    };
+    let period_name = period.display_name(start);
+    let cmdr = cmdr.map(|x| CmdrName::new(&x));
+
+    ctx.defer().await?;
+    let response_from_backend = get_kills_between(&ctx.data().kill_cache, &ctx.data().data_server, start, end).await;
+
+    let message = ctx.send(|builder| {
+        builder.embed(|embed| {
+            match response_from_backend {
+                Err(err) => {
+                    embed.color(Color::RED).title("Error").description(err.to_string())
+                },
+                Ok(data) => {
+                    let heatmap = ActivityHeatmap::from_kills(&data, cmdr.as_ref());
+                    let title = match &cmdr {
+                        Some(cmdr) => format!("Activity of CMDR {} in {}", cmdr, period_name),
+                        None => format!("Activity in {}", period_name)
+                    };
+
+                    match heatmap.peak() {
+                        None => {
+                            let description = match &cmdr {
+                                Some(cmdr) => format!("CMDR {} has no kills or deaths in {}.", cmdr, period_name),
+                                None => format!("Noone has made any kills in {}.", period_name)
+                            };
+                            embed.color(Color::GOLD).title(title).description(description);
+                        },
+                        Some((weekday, hour, count)) => {
+                            let counted = match cmdr {
+                                Some(_) => "kills and deaths",
+                                None => "kills"
+                            };
+                            embed.color(Color::DARK_GREEN).title(title).description(format!(
+                                "```\n{}\n```\n{} {} in total. Busiest hour is {} {:02}:00 with {}.",
+                                heatmap.to_text_grid(), heatmap.total(), counted, weekday, hour, count
+                            ));
+                        }
+                    }
+
+                    embed.footer(|footer| footer.text(format!("Hours are in UTC. Period is from {} to {} (UTC).", start.format("%Y-%m-%d %H:%M"), end.format("%Y-%m-%d %H:%M"))));
+
+                    embed
+                }
+            }
+        })
+    }).await;
+
+    if let Err(reason) = message {
+        println!("Error Sending Message: {:?}", reason)
+    }
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use poise::serenity_prelude::Color;
+
+use crate::{other::{hotspots::get_hotspots, leaderboard_period::LeaderboardPeriod}, kill_cache::sync::get_kills_between, util::text::{to_field_value, truncate, CELL_LIMIT}};
+
+use super::{super::{Context, Error}, pvpleaderboard::time_range_or_reply};
+
+/// Gets the systems with the most kills in a day, week, month, season, since records began or any range
+#[poise::command(slash_command)]
+pub async fn pvphotspots(
+    ctx: Context<'_>,
+    #[description="optional; The period to look at. Defaults to the current leaderboard week, or custom if from is set"] period: Option<LeaderboardPeriod>,
+    #[description="optional; Start of a custom period. YYYY-MM-DD, optionally followed by HH:MM (UTC)"] from: Option<String>,
+    #[description="optional; End of a custom period, inclusive. YYYY-MM-DD, optionally followed by HH:MM (UTC). Defaults to now"] to: Option<String>,
+    #[description="optional; How many systems to show. Defaults to 10"] #[min = 1] #[max = 25] top: Option<u32>
+) -> Result<(), Error> {
+    let period = period.unwrap_or(match from {
+        Some(_) => LeaderboardPeriod::Custom,
+        None => LeaderboardPeriod::Week
+    });
+
+    let (start, end) = match time_range_or_reply(ctx, period, from, to).await? {
+        Some(range) => range,
+        None => return Ok(())
+    };
+    let period_name = period.display_name(start);
+    let top = usize::try_from(top.unwrap_or(10)).unwrap();
+
+    ctx.defer().await?;
+    let response_from_backend = get_kills_between(&ctx.data().kill_cache, &ctx.data().data_server, start, end).await;
+
+    let message = ctx.send(|builder| {
+        builder.embed(|embed| {
+            match response_from_backend {
+                Err(err) => {
+                    embed.color(Color::RED).title("Error").description(err.to_string())
+                },
+                Ok(data) => {
+                    let hotspots = get_hotspots(&data);
+
+                    if hotspots.is_empty() {
+                        embed.color(Color::RED).title("Not Found").description(format!("No kills with a known system in {}.", period_name));
+                    } else {
+                        let mut system_row: Vec<String> = vec![];
+                        let mut kills_row: Vec<String> = vec![];
+                        let mut top_killer_row: Vec<String> = vec![];
+                        for (i, tally) in hotspots.iter().take(top).enumerate() {
+                            system_row.push(format!("{}. {}", i + 1, truncate(&tally.system, CELL_LIMIT)));
+                            kills_row.push(tally.kills.to_string());
+                            top_killer_row.push(match &tally.top_killer {
+                                Some((cmdr, kills)) => format!("{} ({})", truncate(cmdr, CELL_LIMIT), kills),
+                                None => "-".to_string()
+                            });
+                        }
+                        embed.color(Color::DARK_GREEN)
+                            .title(format!("Hotspots for {}", period_name))
+                            .field(":ringed_planet:", to_field_value(&system_row), true)
+                            .field("×:dagger:", to_field_value(&kills_row), true)
+                            .field("Top killer", to_field_value(&top_killer_row), true);
+                    }
+
+                    embed.footer(|footer| footer.text(format!("Period is from {} to {} (UTC). Use /pvpsystem for the kills in a system.", start.format("%Y-%m-%d %H:%M"), end.format("%Y-%m-%d %H:%M"))));
+
+                    embed
+                }
+            }
+        })
+    }).await;
+
+    if let Err(reason) = message {
+        println!("Error Sending Message: {:?}", reason)
+    }
+
+    Ok(())
+}
@@ -0,0 +1,152 @@
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude::Color;
+
+use crate::{other::{leaderboard_period::{LeaderboardPeriod, parse_range_bound, validate_custom_range}, weekly_summary::{get_sorted_summary, LeaderboardMetric}, weekly_summary_event::{build_weekly_embeds, get_top_rows_with_highlight}}, kill_cache::sync::get_kills_between, util::cmdr_name::CmdrName};
+
+use super::{super::{Context, Error}, autocomplete::autocomplete_cmdr};
+
+/// Resolves the options of the command into a time range, or a message for the user why that is not possible.
+/// `covered_from` is the start of the Kill Cache, see [LeaderboardPeriod::time_range].
+pub(super) fn resolve_time_range(period: LeaderboardPeriod, from: Option<String>, to: Option<String>, now: DateTime<Utc>, covered_from: Option<DateTime<Utc>>) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+    if let Some(range) = period.time_range(now, covered_from) {
+        if from.is_some() || to.is_some() {
+            return Err("`from` and `to` can only be used with `period:custom`.".to_string());
+        }
+        return Ok(range);
+    }
+
+    let start = match from {
+        Some(from) => parse_range_bound(&from, false)?,
+        None => return Err("`period:custom` needs at least a `from` date.".to_string())
+    };
+    let end = match to {
+        Some(to) => parse_range_bound(&to, true)?,
+        None => now
+    };
+    validate_custom_range(start, end, now)?;
+
+    return Ok((start, end));
+}
+
+/// Resolves the options of the command into a time range starting no earlier than the Kill Cache does.
+/// If that is not possible, the user is told why and `None` is returned.
+pub(super) async fn time_range_or_reply(ctx: Context<'_>, period: LeaderboardPeriod, from: Option<String>, to: Option<String>) -> Result<Option<(DateTime<Utc>, DateTime<Utc>)>, Error> {
+    let resolved = match ctx.data().kill_cache.covered_from() {
+        Ok(covered_from) => resolve_time_range(period, from, to, chrono::offset::Utc::now(), covered_from),
+        Err(err) => Err(err.to_string())
+    };
+    return match resolved {
+        Ok(range) => Ok(Some(range)),
+        Err(err) => {
+            ctx.send(|x| x.ephemeral(true).embed(|e| e.color(Color::RED).title("Error").description(err))).await?;
+            Ok(None)
+        }
+    };
+}
+
+/// Gets the leaderboard for a day, week, month, season, since records began or any range of dates
+#[poise::command(slash_command)]
+pub async fn pvpleaderboard(
+    ctx: Context<'_>,
+    #[description="optional; The period to rank. Defaults to the current leaderboard week, or custom if from is set"] period: Option<LeaderboardPeriod>,
+    #[description="optional; Start of a custom period. YYYY-MM-DD, optionally followed by HH:MM (UTC)"] from: Option<String>,
+    #[description="optional; End of a custom period, inclusive. YYYY-MM-DD, optionally followed by HH:MM (UTC). Defaults to now"] to: Option<String>,
+    #[description="optional; How many CMDRs to show. Defaults to 10"] #[min = 1] #[max = 25] top: Option<u32>,
+    #[description="optional; Highlight this CMDR in the chosen period"]
+    #[autocomplete = "autocomplete_cmdr"] cmdr_name: Option<String>
+) -> Result<(), Error> {
+    let period = period.unwrap_or(match from {
+        Some(_) => LeaderboardPeriod::Custom,
+        None => LeaderboardPeriod::Week
+    });
+
+    let (start, end) = match time_range_or_reply(ctx, period, from, to).await? {
+        Some(range) => range,
+        None => return Ok(())
+    };
+    let period_name = period.display_name(start);
+    let top = usize::try_from(top.unwrap_or(10)).unwrap();
+    let cmdr_name = cmdr_name.map(|x| CmdrName::new(&x).to_string());
+
+    ctx.defer().await?;
+    let response_from_backend = get_kills_between(&ctx.data().kill_cache, &ctx.data().data_server, start, end).await;
+
+    let message = ctx.send(|builder| {
+        builder.embed(|embed| {
+            match response_from_backend {
+                Err(err) => {
+                    embed.color(Color::RED).title("Error").description(err.to_string())
+                },
+                Ok(data) => {
+                    let result = get_sorted_summary(&data, LeaderboardMetric::Kills);
+                    let (rows, found) = get_top_rows_with_highlight(result, LeaderboardMetric::Kills, top, cmdr_name.as_deref());
+
+                    if rows.is_empty() {
+                        embed.color(Color::RED).title("Not Found").description(format!("Noone has made any kills in {}.", period_name));
+                    } else {
+                        build_weekly_embeds(rows, LeaderboardMetric::Kills, embed);
+                        embed.title(format!("Leaderboard for {}", period_name));
+                        if let (Some(name), false) = (&cmdr_name, found) {
+                            embed.description(format!("CMDR {} has yet to get any kills in {}.", name, period_name));
+                        }
+                    }
+
+                    embed.footer(|footer| footer.text(format!("Leaderboard period is from {} to {} (UTC).", start.format("%Y-%m-%d %H:%M"), end.format("%Y-%m-%d %H:%M"))));
+
+                    embed
+                }
+            }
+        })
+    }).await;
+
+    if let Err(reason) = message {
+        println!("Error Sending Message: {:?}", reason)
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::other::leaderboard_period::LeaderboardPeriod;
+
+    use super::resolve_time_range;
+
+    fn option(value: &str) -> Option<String> {
+        return Some(value.to_string());
+    }
+
+    #[test]
+    fn custom_range_includes_the_last_day() {
+        let now = Utc.with_ymd_and_hms(2023, 12, 14, 12, 0, 0).unwrap();
+        let at = |day: u32, hour: u32| Utc.with_ymd_and_hms(2023, 12, day, hour, 0, 0).unwrap();
+        assert_eq!(resolve_time_range(LeaderboardPeriod::Custom, option("2023-12-01"), option("2023-12-07"), now, None), Ok((at(1, 0), at(8, 0))));
+        assert_eq!(resolve_time_range(LeaderboardPeriod::Custom, option("2023-12-01 08:00"), option("2023-12-07 20:00"), now, None), Ok((at(1, 8), at(7, 20))));
+        // Without an end, the range lasts until now
+        assert_eq!(resolve_time_range(LeaderboardPeriod::Custom, option("2023-12-01"), None, now, None), Ok((at(1, 0), now)));
+        let precise_now = now + chrono::Duration::milliseconds(1500);
+        assert_eq!(resolve_time_range(LeaderboardPeriod::Custom, option("2023-12-01"), None, precise_now, None), Ok((at(1, 0), precise_now)));
+    }
+
+    #[test]
+    fn rejects_unusable_options() {
+        let now = Utc.with_ymd_and_hms(2023, 12, 14, 12, 0, 0).unwrap();
+        let resolve = |period: LeaderboardPeriod, from: Option<String>, to: Option<String>| resolve_time_range(period, from, to, now, None);
+        assert!(resolve(LeaderboardPeriod::Custom, None, option("2023-12-07")).is_err());
+        assert!(resolve(LeaderboardPeriod::Custom, option("2023-12-32"), None).is_err());
+        assert!(resolve(LeaderboardPeriod::Custom, option("2023-12-01"), option("first of june")).is_err());
+        assert!(resolve(LeaderboardPeriod::Custom, option("2023-12-07"), option("2023-12-01")).is_err());
+        assert!(resolve(LeaderboardPeriod::Custom, option("2023-12-20"), None).is_err());
+        assert!(resolve(LeaderboardPeriod::Month, option("2023-12-01"), None).is_err());
+    }
+
+    #[test]
+    fn recorded_falls_back_to_the_cache_coverage() {
+        let now = Utc.with_ymd_and_hms(2023, 12, 14, 12, 0, 0).unwrap();
+        let covered_from = Utc.with_ymd_and_hms(2023, 11, 14, 12, 0, 0).unwrap();
+        assert_eq!(resolve_time_range(LeaderboardPeriod::Recorded, None, None, now, Some(covered_from)), Ok((covered_from, now)));
+        assert_eq!(resolve_time_range(LeaderboardPeriod::Recorded, None, None, now, None).map(|x| x.0), Ok(Utc.with_ymd_and_hms(2014, 12, 16, 0, 0, 0).unwrap()));
+    }
+}
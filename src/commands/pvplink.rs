@@ -0,0 +1,64 @@
+use poise::serenity_prelude::Color;
+
+use crate::{data_server::models::LinkVerification, util::cmdr_name::CmdrName};
+
+use super::super::{Context, Error};
+
+/// Link your Discord account to your CMDR. Upload a Journal with your API Key first.
+#[poise::command(slash_command)]
+pub async fn pvplink(
+    ctx: Context<'_>,
+    #[description="Your CMDR name (without the CMDR Prefix)"] cmdr: String
+) -> Result<(), Error> {
+    let user = ctx.author();
+    let cmdr = CmdrName::new(&cmdr);
+    if cmdr.key().is_empty() {
+        ctx.send(|b| b.ephemeral(true).embed(|e| e.color(Color::RED).title("Error").description("Please enter a CMDR name."))).await?;
+        return Ok(());
+    }
+
+    ctx.defer_ephemeral().await?;
+    let verification = ctx.data().data_server.verify_cmdr_link(user.id.0, cmdr.display()).await;
+
+    match verification {
+        Err(err) => {
+            ctx.send(|b| b.ephemeral(true).embed(|e| {
+                e.color(Color::RED)
+                    .title(":warning: Error occurred")
+                    .description(format!("An Error occurred:\n{}", err))
+            })).await?;
+        },
+        Ok(LinkVerification::NotVerified) => {
+            ctx.send(|b| b.ephemeral(true).embed(|e| {
+                e.color(Color::GOLD)
+                    .title(":octagonal_sign: Not Verified")
+                    .description(format!(
+                        "We could not confirm that you play CMDR {}.\n\nGet an API Key with `/pvpregister`, upload a Journal of CMDR {} with it and try again.",
+                        cmdr, cmdr
+                    ))
+            })).await?;
+        },
+        Ok(LinkVerification::Verified { cmdr_name }) => {
+            // Prefer the spelling of the Journal over whatever the user typed
+            let cmdr = match cmdr_name {
+                Some(name) if cmdr.matches(&name) => CmdrName::new(&name),
+                _ => cmdr
+            };
+            match ctx.data().cmdr_links.link(user.id.0, &cmdr) {
+                Err(err) => {
+                    println!("[ERROR] Failed to store link of {} to CMDR {}: {}", user.id, cmdr, err);
+                    ctx.send(|b| b.ephemeral(true).embed(|e| e.color(Color::RED).title(":warning: Error occurred").description("The link could not be saved. Please try again later."))).await?;
+                },
+                Ok(_) => {
+                    ctx.send(|b| b.ephemeral(true).embed(|e| {
+                        e.color(Color::DARK_GREEN)
+                            .title(":white_check_mark: Linked")
+                            .description(format!("Your Discord account is now linked to CMDR {}.\n\nUse `/pvpwhois` without a name to look yourself up.", cmdr))
+                    })).await?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
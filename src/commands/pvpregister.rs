@@ -1,73 +1,11 @@
-
-
-#[derive(Serialize, Deserialize, Debug)]
-struct OkResponse {
-
-    error: Option<String>,
-    #[serde(rename="isBanned")]
-    is_banned: Option<bool>,
-    token: Option<String>
-}
-
-
-
 use std::time::Duration;
 
-use poise::serenity_prelude::{Color, GuildId, RoleId, User};
-use serde::{Serialize, Deserialize};
+use poise::serenity_prelude::{Color, GuildId, RoleId};
 
-use crate::data::Environment;
+use crate::{data::Environment, data_server::models::TokenGrant};
 
 use super::super::{Context, Error};
 
-enum ErrorType {
-    IsBanned,
-    Other(String)
-}
-
-
-async fn do_request(user: &User) -> Result<String, ErrorType> {
-    let request_form = reqwest::multipart::Form::new()
-    .text("discord_name", user.tag())
-    .text("discord_id", user.id.to_string());
-
-
-    let response = reqwest::Client::new()
-        .post(format!("{}/api/token/discord", Environment::server_address()))
-        .multipart(request_form)
-        .bearer_auth(Environment::server_auth())
-        .send().await.map_err(|x| ErrorType::Other(x.to_string()))?;
-
-    match response.status().is_success() {
-        false => Err(ErrorType::Other(format!("Server responded with a {}", response.status().as_str()))),
-        true => {
-            // Parse as JSON
-            let as_json: OkResponse = response.json().await.map_err(|x|ErrorType::Other(x.to_string()))?;
-            
-            match as_json.is_banned {
-                Some(is_banned) => {
-                    if is_banned {
-                        return Err(ErrorType::IsBanned);
-                    }
-                },
-                None => {}
-            }
-
-            match as_json.token {
-                Some(token) => return Ok(token),
-                None => return Err(ErrorType::Other("No Token was returned".to_string()))
-            }
-        }
-    }
-} 
-
-
-
-
-
-
-
-
 /// Request an API Token. You can also use this command to get
 #[poise::command(slash_command)]
 pub async fn pvpregister(
@@ -90,26 +28,27 @@ pub async fn pvpregister(
         return Ok(());
     }
 
+    // The API Key must only be visible to the user, so every reply from here on is ephemeral
+    ctx.defer_ephemeral().await?;
     // Try to create a new response
-    let response = do_request(user).await;
+    let response = ctx.data().data_server.request_discord_token(user).await;
 
     match response {
+        Ok(TokenGrant::Banned) => {
+            ctx.send(|b|b.embed(|e| {
+                e.color(Color::RED)
+                    .title(":octagonal_sign: User Banned")
+                    .description("Your Discord Account has been sanctioned. No API Key for you.")
+            })).await?;
+        },
         Err(err) => {
-            let heading = match err {
-                ErrorType::IsBanned => ":octagonal_sign: User Banned",
-                ErrorType::Other(_) => ":warning: Error occurred"
-            };
-            let description: String = match err {
-                ErrorType::IsBanned => "Your Discord Account has been sanctioned. No API Key for you.".to_string(),
-                ErrorType::Other(err) => format!("An Error occurred:\n{}", err)
-            };
             ctx.send(|b|b.embed(|e| {
                 e.color(Color::RED)
-                    .title(heading)
-                    .description(description)
+                    .title(":warning: Error occurred")
+                    .description(format!("An Error occurred:\n{}", err))
             })).await?;
         },
-        Ok(token) => {
+        Ok(TokenGrant::Token(token)) => {
             // Got a valid token from the Server
             let sleep_duration_seconds = 60;
             let message = ctx.send(|b|b.ephemeral(true).embed(|e| {
@@ -8,13 +8,22 @@ use super::super::{Context, Error};
 pub async fn pvpself(
     ctx: Context<'_>
 ) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
     let user = ctx.author();
     let is_admin = is_user_admin(&ctx).await;
     let admin_str = match is_admin {
         true => "",
         false => "not "
     };
-    let content = format!("You are {}.\nYour ID is {}\n You are {}an admin.",  convert_tag_to_username(user.tag())  , user.id, admin_str);
+    let linked_cmdr = match ctx.data().cmdr_links.get_cmdr(user.id.0) {
+        Ok(Some(cmdr)) => format!("linked to CMDR {}", cmdr),
+        Ok(None) => "not linked to a CMDR. Use `/pvplink` to link yours".to_string(),
+        Err(err) => {
+            println!("[ERROR] Failed to look up the linked CMDR of {}: {}", user.id, err);
+            "linked to an unknown CMDR, the link could not be loaded".to_string()
+        }
+    };
+    let content = format!("You are {}.\nYour ID is {}\n You are {}an admin.\nYou are {}.",  convert_tag_to_username(user.tag())  , user.id, admin_str, linked_cmdr);
     match ctx.send(|x| x.ephemeral(true).content(content)).await {
         Ok(_) => {},
         Err(err) => println!("Error in pvpself command: {}", err),
@@ -0,0 +1,83 @@
+use poise::serenity_prelude::{Color, CreateEmbed};
+
+use crate::{data::Environment, data_server::models::CmdrHistoryResponse, other::{history::classify_history, ship_stats::get_ship_stats}, util::{cmdr_name::CmdrName, elite::UNKNOWN_SHIP, new_username::convert_tag_to_username}};
+
+use super::{super::{Context, Error}, autocomplete::autocomplete_cmdr};
+
+/// Ships listed in the embed. The rest is summed up in a single line.
+const MAX_SHIPS: usize = 15;
+
+fn apply_ship_stats_to_embed<'a>(response: &CmdrHistoryResponse, embed: &'a mut CreateEmbed) -> &'a mut CreateEmbed {
+    let cmdr = CmdrName::new(&response.cmdr_name);
+    let history = classify_history(&response.history, &cmdr);
+    let min_engagements = Environment::kd_min_engagements();
+    let stats = get_ship_stats(&history, min_engagements);
+
+    embed.color(Color::DARK_GREEN).title(format!("Ships of CMDR {}", cmdr));
+    if stats.ships.is_empty() {
+        return embed.description("No engagements recorded yet.");
+    }
+
+    let most_killed = match &stats.most_killed_enemy_ship {
+        Some((ship, kills)) => format!("{} ({} kills)", ship, kills),
+        None => "-".to_string()
+    };
+    let best_kill_death = match &stats.best_kill_death_ship {
+        Some(tally) => format!("{} ({:.2}, {} kills, {} deaths)", tally.ship.as_deref().unwrap_or(UNKNOWN_SHIP), tally.kill_death_ratio(), tally.kills, tally.deaths),
+        None => format!("- (needs {} engagements in one ship)", min_engagements)
+    };
+    embed.description(format!("**Most killed enemy ship:** {}\n**Best K/D:** {}", most_killed, best_kill_death));
+
+    let mut ship_row: Vec<String> = vec![];
+    let mut score_row: Vec<String> = vec![];
+    let mut kill_death_row: Vec<String> = vec![];
+    for tally in stats.ships.iter().take(MAX_SHIPS) {
+        ship_row.push(tally.ship.clone().unwrap_or_else(|| UNKNOWN_SHIP.to_string()));
+        score_row.push(format!("{} / {}", tally.kills, tally.deaths));
+        kill_death_row.push(format!("{:.2}", tally.kill_death_ratio()));
+    }
+    let other_ships = stats.ships.len().saturating_sub(MAX_SHIPS);
+    if other_ships > 0 {
+        ship_row.push(format!("*…and {} more*", other_ships));
+    }
+
+    embed.field(":rocket:", ship_row.join("\n"), true)
+        .field("×:dagger: / ×:skull:", score_row.join("\n"), true)
+        .field(":dagger:/:skull:", kill_death_row.join("\n"), true);
+    return embed;
+}
+
+/// Kills and deaths of a CMDR by the ship they flew.
+#[poise::command(slash_command)]
+pub async fn pvpshipstats(
+    ctx: Context<'_>,
+    #[description="The CMDR name (without the CMDR Prefix)"]
+    #[autocomplete = "autocomplete_cmdr"] cmdr: String
+) -> Result<(), Error> {
+    let cmdr = CmdrName::new(&cmdr);
+    ctx.defer().await?;
+    let response = ctx.data().data_server.get_cmdr_history(cmdr.display()).await;
+
+    let message = ctx.send(|builder| {
+        builder.embed(|embed| {
+            match response {
+                Err(err) => {
+                    embed.color(Color::RED).title("Error").description(err.to_string())
+                },
+                Ok(None) => {
+                    embed.color(Color::GOLD).title("Not Found").description(format!("We do not have any data for CMDR {}", cmdr))
+                },
+                Ok(Some(response)) => {
+                    apply_ship_stats_to_embed(&response, embed)
+                        .footer(|f| f.text(format!("Asked by {}", convert_tag_to_username(ctx.author().tag()))))
+                }
+            }
+        })
+    }).await;
+
+    if let Err(reason) = message {
+        println!("Error Sending Message: {:?}", reason)
+    }
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use poise::serenity_prelude::{Color, CreateEmbed};
+
+use crate::{data_server::models::RangeResponseEntry, util::{cmdr_name::CmdrName, new_username::convert_tag_to_username, text::{to_field_value, truncate, CELL_LIMIT}}};
+
+use super::super::{Context, Error};
+
+/// Kills listed, newest first.
+const MAX_KILLS: usize = 15;
+
+fn apply_system_kills_to_embed<'a>(system: &str, kills: &[RangeResponseEntry], embed: &'a mut CreateEmbed) -> &'a mut CreateEmbed {
+    // The cache knows how the system is actually spelled
+    let system = kills.first().and_then(|x| x.location.as_deref()).unwrap_or(system);
+    embed.color(Color::DARK_GREEN).title(format!("Recent kills in {}", system));
+
+    let mut killer_row: Vec<String> = vec![];
+    let mut victim_row: Vec<String> = vec![];
+    let mut date_row: Vec<String> = vec![];
+    for kill in kills {
+        killer_row.push(format!(":dagger: {}", truncate(CmdrName::new(&kill.killer_name).display(), CELL_LIMIT)));
+        victim_row.push(format!(":skull: {}", truncate(CmdrName::new(&kill.victim_name).display(), CELL_LIMIT)));
+        date_row.push(kill.timestamp.format("%Y-%m-%d %H:%M:%S").to_string());
+    }
+
+    embed.field("Killer", to_field_value(&killer_row), true)
+        .field("Victim", to_field_value(&victim_row), true)
+        .field("Date", to_field_value(&date_row), true);
+    return embed;
+}
+
+/// Gets the most recent kills in a system
+#[poise::command(slash_command)]
+pub async fn pvpsystem(
+    ctx: Context<'_>,
+    #[description="The name of the system"] name: String
+) -> Result<(), Error> {
+    ctx.defer().await?;
+    let response = ctx.data().kill_cache.kills_in_system(&name, MAX_KILLS);
+
+    let message = ctx.send(|builder| {
+        builder.embed(|embed| {
+            match response {
+                Err(err) => {
+                    embed.color(Color::RED).title("Error").description(err.to_string())
+                },
+                Ok(kills) if kills.is_empty() => {
+                    embed.color(Color::GOLD).title("Not Found").description(format!("We do not have any kills in {}", name.trim()))
+                },
+                Ok(kills) => {
+                    apply_system_kills_to_embed(&name, &kills, embed)
+                        .footer(|f| f.text(format!("Asked by {}", convert_tag_to_username(ctx.author().tag()))))
+                }
+            }
+        })
+    }).await;
+
+    if let Err(reason) = message {
+        println!("Error Sending Message: {:?}", reason)
+    }
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use std::borrow::Cow;
+
+use poise::serenity_prelude::{AttachmentType, Color};
+use plotters::style::RGBColor;
+
+use crate::{charts::{line_chart, ChartLine}, kill_cache::sync::get_kills_between, other::{leaderboard_window::LeaderboardWindow, trends::{kills_per_window, last_windows}}};
+
+use super::super::{Context, Error};
+
+/// File name the chart is attached as.
+const CHART_FILE: &str = "trends.png";
+
+const KILLS_COLOR: RGBColor = RGBColor(88, 101, 242);
+
+/// Gets the total kills of the last leaderboard weeks as a chart
+#[poise::command(slash_command)]
+pub async fn pvptrends(
+    ctx: Context<'_>,
+    #[description="optional; How many leaderboard weeks to show, including the current one. Defaults to 12"] #[min = 2] #[max = 52] weeks: Option<u32>
+) -> Result<(), Error> {
+    let weeks = usize::try_from(weeks.unwrap_or(12)).unwrap();
+    let windows = last_windows(LeaderboardWindow::current(), weeks);
+    let (start, end) = (windows.first().unwrap().start, windows.last().unwrap().end);
+
+    ctx.defer().await?;
+    let response_from_backend = get_kills_between(&ctx.data().kill_cache, &ctx.data().data_server, start, end).await;
+
+    let totals = match &response_from_backend {
+        Ok(data) => kills_per_window(data, &windows),
+        Err(_) => vec![]
+    };
+    let labels: Vec<String> = windows.iter().map(|x| x.start.format("%Y-%m-%d").to_string()).collect();
+    let chart = match &response_from_backend {
+        Err(_) => None,
+        Ok(_) => {
+            let series = [ChartLine { label: "Kills", color: KILLS_COLOR, values: totals.clone() }];
+            match line_chart(&format!("Kills per leaderboard week, last {} weeks", weeks), &labels, &series) {
+                Ok(chart) => Some(chart),
+                Err(err) => {
+                    println!("[WARN] /pvptrends is sent without a chart: {}", err);
+                    None
+                }
+            }
+        }
+    };
+
+    let message = ctx.send(|builder| {
+        if let Some(chart) = &chart {
+            builder.attachment(AttachmentType::Bytes { data: Cow::Borrowed(chart), filename: CHART_FILE.to_string() });
+        }
+        builder.embed(|embed| {
+            match &response_from_backend {
+                Err(err) => {
+                    embed.color(Color::RED).title("Error").description(err.to_string())
+                },
+                Ok(_) => {
+                    let total: u32 = totals.iter().sum();
+                    // The current week is still running, it would drag the average down
+                    let ended_weeks = &totals[..totals.len() - 1];
+                    let average = f64::from(ended_weeks.iter().sum::<u32>()) / ended_weeks.len() as f64;
+                    let week_rows: Vec<String> = labels.iter().zip(totals.iter()).rev()
+                        .map(|(label, kills)| format!("{}: {}", label, kills))
+                        .collect();
+
+                    embed.color(Color::DARK_GREEN)
+                        .title(format!("Kills over the last {} leaderboard weeks", weeks))
+                        .description(format!(
+                            "{} kills in total, {:.1} per ended week on average. {} so far in the current week.",
+                            total, average, totals.last().unwrap()
+                        ))
+                        .field("Week starting", week_rows.join("\n"), false)
+                        .footer(|footer| footer.text(format!("From {} to {} (UTC).", start.format("%Y-%m-%d %H:%M"), end.format("%Y-%m-%d %H:%M"))));
+                    if chart.is_some() {
+                        embed.attachment(CHART_FILE);
+                    }
+                    embed
+                }
+            }
+        })
+    }).await;
+
+    if let Err(reason) = message {
+        println!("Error Sending Message: {:?}", reason)
+    }
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use poise::serenity_prelude::{Color, CreateEmbed};
+
+use crate::{data_server::models::CmdrHistoryResponse, other::{history::{classify_history, HistoryEventType}, versus::get_versus}, util::{cmdr_name::CmdrName, new_username::convert_tag_to_username, text::{to_field_value, truncate, CELL_LIMIT}}};
+
+use super::{super::{Context, Error}, autocomplete::autocomplete_cmdr};
+
+/// Engagements listed in the embed, newest first. Older ones still count towards the score.
+const MAX_ENGAGEMENTS: usize = 15;
+
+/// Systems listed in the description, most engagements first.
+const MAX_SYSTEMS: usize = 5;
+
+fn apply_versus_to_embed<'a>(response_a: &CmdrHistoryResponse, cmdr_b: &CmdrName, embed: &'a mut CreateEmbed) -> &'a mut CreateEmbed {
+    let cmdr_a = CmdrName::new(&response_a.cmdr_name);
+    let history = classify_history(&response_a.history, &cmdr_a);
+    let versus = get_versus(&history, cmdr_b);
+
+    // The history of A knows how B actually spells their name
+    let cmdr_b = match versus.engagements.last() {
+        Some(engagement) => CmdrName::new(&engagement.entry.other_cmdr),
+        None => cmdr_b.clone()
+    };
+
+    embed.color(Color::DARK_GREEN).title(format!("CMDR {} vs CMDR {}", cmdr_a, cmdr_b));
+    let (Some(first_encounter), Some(last_encounter)) = (versus.first_encounter(), versus.last_encounter()) else {
+        return embed.description(format!("CMDR {} and CMDR {} have never met.", cmdr_a, cmdr_b));
+    };
+
+    let mut description = format!(
+        "**Score:** {} {} – {} {}\n**First encounter:** {}\n**Last encounter:** {}",
+        cmdr_a, versus.kills_a(), versus.kills_b(), cmdr_b, first_encounter, last_encounter
+    );
+    if !versus.systems.is_empty() {
+        let systems: Vec<String> = versus.systems.iter()
+            .take(MAX_SYSTEMS)
+            .map(|(system, count)| format!("{} ({}×)", truncate(system, CELL_LIMIT), count))
+            .collect();
+        description.push_str(&format!("\n**Systems:** {}", systems.join(", ")));
+    }
+    description.push_str("\n\n**__Engagements__**");
+    embed.description(description);
+
+    let mut result_row: Vec<String> = vec![];
+    let mut score_row: Vec<String> = vec![];
+    let mut date_row: Vec<String> = vec![];
+    for engagement in versus.engagements.iter().rev().take(MAX_ENGAGEMENTS) {
+        let result = match engagement.entry.event_type {
+            HistoryEventType::Killed => format!(":dagger: {}", truncate(cmdr_a.display(), CELL_LIMIT)),
+            HistoryEventType::Died => format!(":dagger: {}", truncate(cmdr_b.display(), CELL_LIMIT))
+        };
+        result_row.push(result);
+        score_row.push(format!("{} – {}", engagement.score_a, engagement.score_b));
+        date_row.push(engagement.entry.timestamp.clone());
+    }
+    let earlier = versus.engagements.len().saturating_sub(MAX_ENGAGEMENTS);
+    if earlier > 0 {
+        result_row.push(format!("*…and {} earlier*", earlier));
+    }
+
+    embed.field("Won by", to_field_value(&result_row), true)
+        .field("Score", to_field_value(&score_row), true)
+        .field("Date", to_field_value(&date_row), true);
+    return embed;
+}
+
+/// Every recorded engagement between two CMDRs.
+#[poise::command(slash_command)]
+pub async fn pvpversus(
+    ctx: Context<'_>,
+    #[description="The first CMDR name (without the CMDR Prefix)"]
+    #[autocomplete = "autocomplete_cmdr"] cmdr_a: String,
+    #[description="The second CMDR name (without the CMDR Prefix)"]
+    #[autocomplete = "autocomplete_cmdr"] cmdr_b: String
+) -> Result<(), Error> {
+    let cmdr_a = CmdrName::new(&cmdr_a);
+    let cmdr_b = CmdrName::new(&cmdr_b);
+    ctx.defer().await?;
+    let response = match cmdr_a == cmdr_b {
+        true => None,
+        false => Some(ctx.data().data_server.get_cmdr_history(cmdr_a.display()).await)
+    };
+
+    let message = ctx.send(|builder| {
+        builder.embed(|embed| {
+            match response {
+                None => {
+                    embed.color(Color::GOLD).title("Same CMDR").description(format!("Pick two different CMDRs, not CMDR {} twice.", cmdr_a))
+                },
+                Some(Err(err)) => {
+                    embed.color(Color::RED).title("Error").description(err.to_string())
+                },
+                Some(Ok(None)) => {
+                    embed.color(Color::GOLD).title("Not Found").description(format!("We do not have any data for CMDR {}", cmdr_a))
+                },
+                Some(Ok(Some(response))) => {
+                    apply_versus_to_embed(&response, &cmdr_b, embed)
+                        .footer(|f| f.text(format!("Asked by {}", convert_tag_to_username(ctx.author().tag()))))
+                }
+            }
+        })
+    }).await;
+
+    if let Err(reason) = message {
+        println!("Error Sending Message: {:?}", reason)
+    }
+
+    Ok(())
+}
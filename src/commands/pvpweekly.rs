@@ -1,101 +1,128 @@
-use poise::serenity_prelude::Color;
+use std::borrow::Cow;
 
-use crate::other::{weekly_summary::{get_kills_for_current_cycle, get_sorted_weekly_summary, get_relevant_time_range}, weekly_summary_event::{build_weekly_embeds, is_cmdr_in_result_set}};
+use poise::serenity_prelude::{AttachmentType, Color, CreateEmbed};
 
-use super::super::{Context, Error};
+use crate::other::{weekly_summary::{get_kills_for_window, get_sorted_summary, LeaderboardMetric}, weekly_summary_event::{build_top_chart, build_weekly_embeds, get_rows_to_present, TOP_CHART_FILE}, leaderboard_window::{LeaderboardWindow, WindowAnchor}};
+
+use crate::{data::Environment, data_server::models::RangeResponseEntry, kill_cache::KillCacheError, util::cmdr_name::{closest_names, CmdrName}};
+
+use super::{super::{Context, Error}, autocomplete::autocomplete_cmdr, did_you_mean::{add_suggestion_buttons, handle_suggestion_clicks, MAX_DID_YOU_MEAN}, interaction::interaction_id_prefix};
 
 pub struct DataRow {
     pub position: u32,
     pub cmdr: String,
-    pub kill_count: u32,
+    pub score: String,
     pub highlight: bool
 }
 
 
+/// Builds the leaderboard embed. If the CMDR is not on the leaderboard, the closest names on it are returned as suggestions.
+fn build_leaderboard(response_from_backend: &Result<Vec<RangeResponseEntry>, KillCacheError>, metric: LeaderboardMetric, cmdr_name: Option<&str>, window: &LeaderboardWindow) -> (CreateEmbed, Vec<String>) {
+    let mut embed = CreateEmbed::default();
+    let mut suggestions: Vec<String> = vec![];
+
+    match response_from_backend {
+        Err(err) => {
+            embed.color(Color::RED).title("Error").description(err.to_string());
+        },
+        Ok(data) => {
+            let result = get_sorted_summary(data, metric);
+            if let Some(name) = cmdr_name {
+                suggestions = closest_names(name, result.iter().map(|x| x.tally.cmdr.as_str()), MAX_DID_YOU_MEAN);
+            }
+
+            let data_to_present = get_rows_to_present(result, metric, cmdr_name);
+
+            fn create_not_found_error(name: Option<&str>, metric: LeaderboardMetric, has_suggestions: bool, embed: &mut CreateEmbed) {
+                let description = match (name, metric) {
+                    (None, _) => "Noone has made any kills for this leaderboard period… *yet.".to_string(),
+                    (Some(name), LeaderboardMetric::KillDeathRatio) => {
+                        format!("CMDR {} needs at least {} kills and deaths combined in this leaderboard period to get a K/D ranking.", name, Environment::kd_min_engagements())
+                    },
+                    (Some(name), LeaderboardMetric::Deaths) => {
+                        format!("CMDR {} has yet to die in this leaderboard period.", name)
+                    },
+                    (Some(name), _) => {
+                        let message = format!("CMDR {} has yet to get any kills in this leaderboard period.", name);
+                        message
+                    }
+                };
+                let description = match has_suggestions {
+                    true => format!("{}\n\nDid you mean one of these?", description),
+                    false => description
+                };
+
+                embed.color(Color::RED).title("Not Found").description(description);
+            }
+
+            match data_to_present {
+                None => create_not_found_error(cmdr_name, metric, !suggestions.is_empty(), &mut embed),
+                Some(data) => {
+                    suggestions.clear();
+                    build_weekly_embeds(data, metric, &mut embed);
+                    if metric != LeaderboardMetric::Kills {
+                        embed.title(format!("{} in current leaderboard week", metric.title()));
+                    }
+                },
+            };
+
+            embed.footer(|footer| footer.text(format!("Current leaderboard period is from {} to {}, starting {}.", window.start.format("%Y-%m-%d"), window.end.format("%Y-%m-%d"), WindowAnchor::from_environment())));
+        },
+    }
+    return (embed, suggestions);
+}
+
 /// Gets the leaderboard for current game week (ends on Thursdays 8AM UTC)
 #[poise::command(slash_command)]
 pub async fn pvpweekly(
     ctx: Context<'_>,
-    #[description="optional; Get Ranking for CMDR in the current leaderboard period"] cmdr_name: Option<String>
+    #[description="optional; Get Ranking for CMDR in the current leaderboard period"]
+    #[autocomplete = "autocomplete_cmdr"] cmdr_name: Option<String>,
+    #[description="optional; Rank by kills, deaths, K/D or net score (kills minus deaths). Defaults to kills"] metric: Option<LeaderboardMetric>
 ) -> Result<(), Error> {
+    let metric = metric.unwrap_or(LeaderboardMetric::Kills);
+    let cmdr_name = cmdr_name.map(|x| CmdrName::new(&x).to_string());
 
-    let response_from_backend = get_kills_for_current_cycle().await;
-    let time_range_utc = get_relevant_time_range();
-    let time_range_date = (time_range_utc.0.format("%Y-%m-%d"), time_range_utc.1.format("%Y-%m-%d"));
-
-    let message = ctx.send(|builder| {
-        builder.embed(|embed: &mut poise::serenity_prelude::CreateEmbed| {
-            match response_from_backend {
-                Err(err) => {
-                    embed.color(Color::RED).title("Error").description(err)
-                },
-                Ok(data) => {
-                    let result: Vec<(String, u32)> = get_sorted_weekly_summary(data);
-                    
-                    let data_to_present = match cmdr_name {
-                        Some(ref name) => match is_cmdr_in_result_set(&result, &name) {
-                            false => None,
-                            true => {
-                                let mut queue: Vec<(u32, &str, u32)> = vec![];
-                                
-                                for(i, (el_str, el_count)) in result.iter().enumerate() {
-                                    queue.push((u32::try_from(i).unwrap(), el_str.as_str(), *el_count));
-                                    if el_str.eq_ignore_ascii_case(&name) {
-                                        break
-                                    }
-                                }
-
-                                let data = queue.into_iter().rev().take(5).enumerate().map(|(i,e)| DataRow {
-                                    position: e.0 + 1,
-                                    cmdr: e.1.to_owned(),
-                                    kill_count: e.2,
-                                    highlight: i == 0
-                                }).rev().collect::<Vec<_>>();
-                                
-                                Some(data)
-                            }
-                        },
-                        None => {
-                            let data = result.into_iter().enumerate().take(10).map(|(i,e)| DataRow {
-                                position: u32::try_from(i).unwrap() + 1,
-                                cmdr: e.0.to_owned(),
-                                kill_count: e.1,
-                                highlight: false
-                            }).collect::<Vec<_>>();
-                            Some(data)
-                        },
-                    };
-
-                    fn create_not_found_error<'str_life>(name: &Option<String>, embed: &mut poise::serenity_prelude::CreateEmbed) {
-                        let description = match name {
-                            None => "Noone has made any kills for this leaderboard period… *yet.".to_string(),
-                            Some(name) => {
-                                let message = format!("CMDR {} has yet to get any kills in this leaderboard period.", name);
-                                message
-                            }
-                        };
-
-                        embed.color(Color::RED).title("Not Found").description(description);
-                    }
+    ctx.defer().await?;
+    let window = LeaderboardWindow::current();
+    let response_from_backend = get_kills_for_window(ctx.data(), &window).await;
 
+    let (mut embed, suggestions) = build_leaderboard(&response_from_backend, metric, cmdr_name.as_deref(), &window);
 
-                    match data_to_present {
-                        None => create_not_found_error(&cmdr_name, embed),
-                        Some(data) => {
-                            build_weekly_embeds(data, embed);
-                        },
-                    };
+    // Only the plain top 10 gets a chart. Around a CMDR, the excerpt of the leaderboard is too short to be worth one.
+    let chart = match (&response_from_backend, &cmdr_name) {
+        (Ok(data), None) => build_top_chart(&get_sorted_summary(data, metric), metric, 10, &format!("{} in current leaderboard week", metric.title())),
+        _ => None
+    };
+    if chart.is_some() {
+        embed.attachment(TOP_CHART_FILE);
+    }
 
-                    embed.footer(|footer| footer.text(format!("Current leaderboard period is from {} to {}, starting at 8AM UTC.", time_range_date.0, time_range_date.1)));
+    let id_prefix = interaction_id_prefix(&ctx);
+    let message = ctx.send(|builder| {
+        if let Some(chart) = &chart {
+            builder.attachment(AttachmentType::Bytes { data: Cow::Borrowed(chart), filename: TOP_CHART_FILE.to_string() });
+        }
+        builder
+            .embed(|e| {
+                *e = embed;
+                e
+            })
+            .components(|c| add_suggestion_buttons(c, &id_prefix, &suggestions))
+    }).await;
 
-                    embed
-                },
+    match message {
+        Err(reason) => println!("Error Sending Message: {:?}", reason),
+        Ok(reply) => {
+            // The kills of the week are already loaded, so choosing a suggestion only ranks them again
+            let lookup = |name: String| {
+                let result = build_leaderboard(&response_from_backend, metric, Some(&name), &window);
+                async move { result }
+            };
+            if let Err(reason) = handle_suggestion_clicks(ctx, reply, suggestions, lookup).await {
+                println!("Error handling a did you mean suggestion: {:?}", reason)
             }
-        })
-    } ).await;
-    
-    if let Err(reason) = message {
-        println!("Error Sending Message: {:?}", reason)
+        }
     }
 
     Ok(())
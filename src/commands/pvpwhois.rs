@@ -1,90 +1,57 @@
-use poise::serenity_prelude::{CreateEmbed, Color};
-use reqwest::StatusCode;
-use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 
-use crate::util::new_username::convert_tag_to_username;
+use plotters::style::RGBColor;
+use poise::serenity_prelude::{AttachmentType, ButtonStyle, CreateComponents, CreateEmbed, Color, InteractionResponseType, User};
+use crate::{charts::{line_chart, ChartLine}, util::{new_username::convert_tag_to_username, cmdr_name::CmdrName, text::{to_field_value, truncate, CELL_LIMIT}}, data_server::models::CmdrWhoisLookupResponseSuccess, other::{history::{classify_history, HistoryEntry, HistoryEventType}, rivals::{get_rivals, Rivals}, trends::monthly_timeline}};
 
-use super::super::{Context, Error};
+use super::{super::{Context, Error}, autocomplete::autocomplete_cmdr, did_you_mean::{add_suggestion_buttons, chosen_suggestion, MAX_DID_YOU_MEAN}, interaction::{add_attachment, await_click, interaction_id_prefix, set_components}};
 
-#[derive(Debug, Serialize, Deserialize)]
-enum HistoryEventType {
-    Died,
-    Killed
+/// How much of the recent history `/pvpwhois` shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum WhoisLayout {
+    /// One line per engagement: CMDR, system and date
+    #[name = "compact"]
+    Compact,
+    /// Ships and combat ranks of both sides
+    #[name = "detailed"]
+    Detailed
 }
 
+/// Entries of the recent history shown per page.
+const HISTORY_PAGE_SIZE: usize = 10;
 
-struct HistoryEntry {
-    event_type: HistoryEventType,
-    other_cmdr: String,
-    timestamp: String,
-    location: Option<String>
-}
+impl CmdrWhoisLookupResponseSuccess {
 
-impl HistoryEntry {
-    fn from_json(data: &HistoryEntryJson, self_cmdr: String) -> Option<Self> {
-        let event_type: HistoryEventType;
-        if self_cmdr.eq_ignore_ascii_case(&data.killer_name) {
-            event_type = HistoryEventType::Killed;
-        } else if self_cmdr.eq_ignore_ascii_case(&data.victim_name) {
-            event_type = HistoryEventType::Died;
-        } else {
-            return None;
-        }
-        let event_type = event_type;
-        let other_cmdr = match event_type {
-            HistoryEventType::Killed => data.victim_name.clone(),
-            HistoryEventType::Died => data.killer_name.clone()
-        };
-        let location: Option<String>;
-        match data.location.clone() {
-            None => location = None,
-            Some(val) => {
-                if val.to_lowercase().contains("unknown") {
-                    location = None;
-                }
-                else {
-                    location = Some(val);
-                }
-            }
-        }
-        return Some(HistoryEntry::new(event_type, other_cmdr, data.timestamp.clone(), location));
+    fn history(&self) -> Vec<HistoryEntry> {
+        return classify_history(&self.recent_history, &CmdrName::new(&self.cmdr_name));
     }
 
-    pub fn new(event_type: HistoryEventType, other_cmdr: String, timestamp: String, location: Option<String>) -> Self {
-        HistoryEntry { event_type, other_cmdr, timestamp, location }
+    fn page_count(&self) -> usize {
+        return self.history().len().div_ceil(HISTORY_PAGE_SIZE).max(1);
     }
-}
-
-
-#[derive(Debug, Serialize, Deserialize)]
-struct HistoryEntryJson {
-    timestamp: String,
-    killer_name: String,
-    killer_ship: String,
-    killer_rank: String,
-    victim_name: String,
-    victim_ship: String,
-    victim_rank: String,
-    location: Option<String>
-}
-
-
-
-#[derive(Debug, Serialize, Deserialize)]
-struct CmdrWhoisLookupResponseSuccess {
-    #[serde(rename="cmdrName")]
-    cmdr_name: String,
-    kills: u32,
-    deaths: u32,
-    #[serde(rename="recentHistory")]
-    recent_history: Vec<HistoryEntryJson>
-}
-
-impl CmdrWhoisLookupResponseSuccess {
 
+    /// Kills and deaths, the rivals if known, and the heading of the history below.
+    fn apply_description(&self, embed: &mut CreateEmbed, rivals: Option<&Rivals>, history_heading: &str) {
+        let mut description = format!(" :dagger: × {} :skull: × {}", self.kills, self.deaths);
+        if let Some(rivals) = rivals {
+            let mut lines: Vec<String> = vec![];
+            if let Some(nemesis) = &rivals.nemesis {
+                lines.push(format!(":skull: Nemesis: **{}**, killed them {}× (head-to-head {}–{})", truncate(&nemesis.cmdr, CELL_LIMIT), nemesis.deaths, nemesis.kills, nemesis.deaths));
+            }
+            if let Some(target) = &rivals.favourite_target {
+                lines.push(format!(":dagger: Favourite target: **{}**, killed {}× (head-to-head {}–{})", truncate(&target.cmdr, CELL_LIMIT), target.kills, target.kills, target.deaths));
+            }
+            if !lines.is_empty() {
+                description.push_str(&format!("\n\n**__Rivals__**\n{}", lines.join("\n")));
+            }
+        }
+        embed.description(format!("{} {}", description, history_heading));
+    }
 
-    fn apply_to_embed(self, embed: &mut CreateEmbed, callee: String) -> &mut CreateEmbed {
-        let historic_data: Vec<HistoryEntry> = self.recent_history.iter().filter_map(|f| HistoryEntry::from_json(f, self.cmdr_name.clone())).collect();
+    /// Shows the stats of the CMDR and one page of their recent history. `page` starts at 0.
+    fn apply_to_embed<'a>(&self, embed: &'a mut CreateEmbed, callee: &str, page: usize, layout: WhoisLayout, rivals: Option<&Rivals>) -> &'a mut CreateEmbed {
+        let self_cmdr = CmdrName::new(&self.cmdr_name);
+        let historic_data = self.history();
         let historic_data_len = historic_data.len();
         let mut cmdr_row: Vec<String> = vec![];
         let mut system_row: Vec<String> = vec![];
@@ -92,109 +59,398 @@ impl CmdrWhoisLookupResponseSuccess {
 
         let mut has_system_data = false;
 
-        for entry in historic_data {
+        let page_entries: Vec<HistoryEntry> = historic_data.into_iter().skip(page * HISTORY_PAGE_SIZE).take(HISTORY_PAGE_SIZE).collect();
+        if layout == WhoisLayout::Detailed {
+            return self.apply_detailed_to_embed(embed, callee, page, page_entries, rivals);
+        }
+
+        for entry in page_entries {
             let emoji_to_use = match entry.event_type {
                 HistoryEventType::Killed => ":dagger:",
                 HistoryEventType::Died => ":skull:"
             };
-            cmdr_row.push(format!("{} {}", emoji_to_use, entry.other_cmdr));
+            cmdr_row.push(format!("{} {}", emoji_to_use, truncate(&entry.other_cmdr, CELL_LIMIT)));
             let location_string = match entry.location {
                 Some(location) => {
                     has_system_data = true;
-                    location
+                    truncate(&location, CELL_LIMIT)
                 },
                 None => "".to_string()
             };
             system_row.push(location_string);
             date_row.push(entry.timestamp);
-            //system_row.push(location_string);
-            //timestamp_row.push(format!("{}\n", entry.timestamp));
         }
 
         let description_as_heading = match historic_data_len {
             0 => "",
             _ => "\n\n**__Recent History__**\n\n"
         };
-        embed.title(format!("CMDR {}", self.cmdr_name));
-        embed.description(format!(" :dagger: × {} :skull: × {} {}", self.kills, self.deaths, description_as_heading));
+        embed.title(format!("CMDR {}", self_cmdr));
+        self.apply_description(embed, rivals, description_as_heading);
         if historic_data_len > 0 {
-            embed.field(":busts_in_silhouette:", cmdr_row.join("\n"), true);
+            embed.field(":busts_in_silhouette:", to_field_value(&cmdr_row), true);
             if has_system_data {
-                embed.field(":ringed_planet:", system_row.join("\n"), true);
+                embed.field(":ringed_planet:", to_field_value(&system_row), true);
             }
-            embed.field(":calendar_spiral:", date_row.join("\n"), true);
+            embed.field(":calendar_spiral:", to_field_value(&date_row), true);
         }
-        embed.footer(|f| f.text(format!("Asked by {}", callee)));
-    
+        self.apply_footer(embed, callee, page);
+
         return embed;
     }
 
-    pub async fn get_from_server(cmdr_name: &String) -> Result<Option<Self>, String> {
-        let safe_cmdr_name = urlencoding::encode(&cmdr_name.as_str()).into_owned();
-        let server_url = crate::data::Environment::server_address();
-        let server_auth = crate::data::Environment::server_auth();
-
-        async fn run_request(path: String, server_auth: String) -> Result<Option<CmdrWhoisLookupResponseSuccess>, reqwest::Error> {
-            let response = reqwest::Client::new()
-            .get(path)
-            .bearer_auth(server_auth)
-            .send()
-            .await;
-
-            match response {
-                Ok(response_ok) => {
-                    if response_ok.status() == StatusCode::NOT_FOUND {
-                        return Ok(None);
-                    }
-
-                    let parsed_response: CmdrWhoisLookupResponseSuccess = 
-                    response_ok
-                        .json()
-                        .await?;
-                    return Ok(Some(parsed_response));
-                },
-                Err(bad_response) => return Err(bad_response)
+    /// One field per engagement, with ships and ranks of both sides.
+    fn apply_detailed_to_embed<'a>(&self, embed: &'a mut CreateEmbed, callee: &str, page: usize, page_entries: Vec<HistoryEntry>, rivals: Option<&Rivals>) -> &'a mut CreateEmbed {
+        let description_as_heading = match page_entries.len() {
+            0 => "",
+            _ => "\n\n**__Recent History__**"
+        };
+        embed.title(format!("CMDR {}", CmdrName::new(&self.cmdr_name)));
+        self.apply_description(embed, rivals, description_as_heading);
+
+        for entry in page_entries {
+            let (emoji_to_use, verb) = match entry.event_type {
+                HistoryEventType::Killed => (":dagger:", "killed"),
+                HistoryEventType::Died => (":skull:", "was killed by")
+            };
+            let name = format!("{} {} {} · {}", emoji_to_use, verb, truncate(&entry.other_cmdr, CELL_LIMIT), entry.timestamp);
+            let mut value = format!(
+                "Flying a {} against a {}",
+                truncate(&entry.own_pilot(), CELL_LIMIT),
+                truncate(&entry.other_pilot(), CELL_LIMIT)
+            );
+            if let Some(location) = entry.location {
+                value.push_str(&format!("\n:ringed_planet: {}", truncate(&location, CELL_LIMIT)));
+            }
+            embed.field(name, value, false);
+        }
+        self.apply_footer(embed, callee, page);
+
+        return embed;
+    }
+
+    fn apply_footer(&self, embed: &mut CreateEmbed, callee: &str, page: usize) {
+        match self.page_count() {
+            1 => embed.footer(|f| f.text(format!("Asked by {}", callee))),
+            page_count => embed.footer(|f| f.text(format!("Page {} of {} · Asked by {}", page + 1, page_count, callee)))
+        };
+    }
+}
+
+/// File name the kills and deaths timeline is attached as.
+const TIMELINE_FILE: &str = "timeline.png";
+
+const KILLS_COLOR: RGBColor = RGBColor(46, 160, 67);
+const DEATHS_COLOR: RGBColor = RGBColor(218, 54, 51);
+
+/// Kills and deaths per month over the complete history. `None` without any history or if drawing failed.
+fn render_timeline(cmdr: &CmdrName, history: &[HistoryEntry]) -> Option<Vec<u8>> {
+    let timeline = monthly_timeline(history);
+    if timeline.is_empty() {
+        return None;
+    }
+    let labels: Vec<String> = timeline.iter().map(|x| x.month.format("%Y-%m").to_string()).collect();
+    let series = [
+        ChartLine { label: "Kills", color: KILLS_COLOR, values: timeline.iter().map(|x| x.kills).collect() },
+        ChartLine { label: "Deaths", color: DEATHS_COLOR, values: timeline.iter().map(|x| x.deaths).collect() }
+    ];
+    return match line_chart(&format!("CMDR {} per month", cmdr), &labels, &series) {
+        Ok(chart) => Some(chart),
+        Err(err) => {
+            println!("[WARN] /pvpwhois is sent without a timeline: {}", err);
+            None
+        }
+    };
+}
+
+/// What `/pvpwhois` currently shows.
+enum WhoisResult {
+    /// `rivals` and `timeline` are `None` if the complete history could not be loaded
+    Found { response: CmdrWhoisLookupResponseSuccess, rivals: Option<Rivals>, timeline: Option<Vec<u8>>, page: usize },
+    NotFound { cmdr: CmdrName, suggestions: Vec<String> },
+    Failed(String)
+}
+
+impl WhoisResult {
+
+    /// Looks up a CMDR. If the Data Server does not know them, the closest known names are suggested.
+    /// Rivals and timeline are computed from the complete history, which is loaded alongside. Without it, the lookup still succeeds.
+    async fn lookup(ctx: &Context<'_>, cmdr: CmdrName) -> Self {
+        let data_server = &ctx.data().data_server;
+        let (response, history) = tokio::join!(data_server.get_cmdr(cmdr.display()), data_server.get_cmdr_history(cmdr.display()));
+
+        let (rivals, timeline) = match history {
+            Ok(Some(history)) => {
+                let history_cmdr = CmdrName::new(&history.cmdr_name);
+                let history = classify_history(&history.history, &history_cmdr);
+                (Some(get_rivals(&history)), render_timeline(&history_cmdr, &history))
+            },
+            Ok(None) => (None, None),
+            Err(err) => {
+                println!("[WARN] Failed to load the history of CMDR {} for their rivals: {}", cmdr, err);
+                (None, None)
+            }
+        };
+
+        return match response {
+            Err(err) => WhoisResult::Failed(err.to_string()),
+            Ok(Some(response)) => WhoisResult::Found { response, rivals, timeline, page: 0 },
+            Ok(None) => {
+                let suggestions = ctx.data().kill_cache.closest_cmdrs(cmdr.display(), MAX_DID_YOU_MEAN);
+                WhoisResult::NotFound { cmdr, suggestions }
             }
+        };
+    }
 
+    fn to_embed(&self, callee: &str, layout: WhoisLayout) -> CreateEmbed {
+        let mut embed = CreateEmbed::default();
+        match self {
+            WhoisResult::Failed(err) => {
+                embed.color(Color::RED).title("Error").description(err);
+            },
+            WhoisResult::Found { response, rivals, page, .. } => {
+                response.apply_to_embed(&mut embed, callee, *page, layout, rivals.as_ref()).color(Color::DARK_GREEN);
+            },
+            WhoisResult::NotFound { cmdr, suggestions } => {
+                let description = match suggestions.is_empty() {
+                    true => format!("We do not have any data for CMDR {}", cmdr),
+                    false => format!("We do not have any data for CMDR {}. Did you mean one of these?", cmdr)
+                };
+                embed.color(Color::GOLD).title("Not Found").description(description);
+            }
         }
-        
-        let response = run_request(format!("{}/api/bot/user/{}", server_url, safe_cmdr_name), server_auth).await;
-        match response {
-            Ok(good_response) => Ok(good_response),
-            Err(bad_response) => Err(bad_response.to_string())
+        return embed;
+    }
+
+    fn timeline(&self) -> Option<&Vec<u8>> {
+        return match self {
+            WhoisResult::Found { timeline, .. } => timeline.as_ref(),
+            _ => None
+        };
+    }
+
+    /// Whether there is anything to click.
+    fn is_interactive(&self) -> bool {
+        return match self {
+            WhoisResult::Found { response, .. } => response.page_count() > 1,
+            WhoisResult::NotFound { suggestions, .. } => !suggestions.is_empty(),
+            WhoisResult::Failed(_) => false
+        };
+    }
+
+    /// Previous/Next buttons for the history, or the did you mean buttons. Once `enabled` is false, page buttons stay visible but can no longer be clicked.
+    fn add_buttons<'a>(&self, components: &'a mut CreateComponents, id_prefix: &str, enabled: bool) -> &'a mut CreateComponents {
+        match self {
+            WhoisResult::Found { response, page, .. } => {
+                let page_count = response.page_count();
+                if page_count > 1 {
+                    components.create_action_row(|row| row
+                        .create_button(|b| b.style(ButtonStyle::Secondary).label("Previous").custom_id(format!("{}-{}", id_prefix, PREVIOUS_PAGE)).disabled(!enabled || *page == 0))
+                        .create_button(|b| b.style(ButtonStyle::Secondary).label("Next").custom_id(format!("{}-{}", id_prefix, NEXT_PAGE)).disabled(!enabled || *page + 1 >= page_count))
+                    );
+                }
+            },
+            WhoisResult::NotFound { suggestions, .. } => {
+                if enabled {
+                    add_suggestion_buttons(components, id_prefix, suggestions);
+                }
+            },
+            WhoisResult::Failed(_) => {}
         }
+        return components;
     }
 }
 
-/// Look up a CMDR on the bot.
+const PREVIOUS_PAGE: &str = "previous";
+const NEXT_PAGE: &str = "next";
+
+/// Resolves the options of `/pvpwhois` to a CMDR, or a message for the user why that is not possible.
+/// Without a name or user, the CMDR linked to the author is looked up.
+fn resolve_cmdr(ctx: &Context<'_>, cmdr: Option<String>, user: Option<User>) -> Result<CmdrName, String> {
+    let (discord_user, is_author) = match (cmdr, user) {
+        (Some(_), Some(_)) => return Err("Enter either a CMDR name or a user, not both.".to_string()),
+        (Some(cmdr), None) => return Ok(CmdrName::new(&cmdr)),
+        (None, Some(user)) => {
+            let is_author = user.id == ctx.author().id;
+            (user, is_author)
+        },
+        (None, None) => (ctx.author().clone(), true)
+    };
+
+    return match ctx.data().cmdr_links.get_cmdr(discord_user.id.0) {
+        Ok(Some(cmdr)) => Ok(cmdr),
+        Ok(None) => match is_author {
+            true => Err("You have not linked a CMDR yet. Enter a CMDR name, or link your own CMDR with `/pvplink`.".to_string()),
+            false => Err(format!("{} has not linked a CMDR yet. Look them up by CMDR name instead, or ask them to use `/pvplink`.", convert_tag_to_username(discord_user.tag())))
+        },
+        Err(err) => {
+            println!("[ERROR] Failed to look up the linked CMDR of {}: {}", discord_user.id, err);
+            Err("The linked CMDR could not be loaded. Please enter a CMDR name.".to_string())
+        }
+    };
+}
+
+/// Look up a CMDR on the bot, by name or by Discord user. Defaults to your own linked CMDR.
 #[poise::command(slash_command)]
 pub async fn pvpwhois(
     ctx: Context<'_>,
-    #[description="The CMDR name (without the CMDR Prefix)"] cmdr: String
+    #[description="optional; The CMDR name (without the CMDR Prefix). Defaults to your linked CMDR"]
+    #[autocomplete = "autocomplete_cmdr"] cmdr: Option<String>,
+    #[description="optional; A Discord user that linked their CMDR with /pvplink"] user: Option<User>,
+    #[description="optional; compact, or detailed with ships and combat ranks. Defaults to compact"] layout: Option<WhoisLayout>
 ) -> Result<(), Error> {
+    let layout = layout.unwrap_or(WhoisLayout::Compact);
 
-    let response =  CmdrWhoisLookupResponseSuccess::get_from_server(&cmdr).await;
+    let cmdr = match resolve_cmdr(&ctx, cmdr, user) {
+        Ok(cmdr) => cmdr,
+        Err(reason) => {
+            ctx.send(|x| x.ephemeral(true).embed(|e| e.color(Color::GOLD).title("No CMDR to look up").description(reason))).await?;
+            return Ok(());
+        }
+    };
+    ctx.defer().await?;
+    let callee = convert_tag_to_username(ctx.author().tag());
+    let mut result = WhoisResult::lookup(&ctx, cmdr).await;
 
+    let id_prefix = interaction_id_prefix(&ctx);
     let message = ctx.send(|builder| {
+        if let Some(timeline) = result.timeline() {
+            builder.attachment(AttachmentType::Bytes { data: Cow::Borrowed(timeline), filename: TIMELINE_FILE.to_string() });
+        }
         builder
-            .embed(|embed| {
-                match response {
-                    Err(err) => {
-                        embed.color(Color::RED).title("Error").description(err)
-                    },
-                    Ok(data) => {
-                        match data {
-                            Some(response) => response.apply_to_embed(embed, convert_tag_to_username(ctx.author().tag())).color(Color::DARK_GREEN),
-                            None => embed.color(Color::GOLD).title("Not Found").description(format!("We do not have any data for CMDR {}", &cmdr).as_str())
-                        }
-                    }
-                }
+            .embed(|e| {
+                *e = result.to_embed(&callee, layout);
+                e
             })
+            .components(|c| result.add_buttons(c, &id_prefix, true))
     }).await;
 
-    if let Err(reason) = message {
-        println!("Error Sending Message: {:?}", reason)
+    let reply = match message {
+        Ok(reply) => reply,
+        Err(reason) => {
+            println!("Error Sending Message: {:?}", reason);
+            return Ok(());
+        }
+    };
+
+    while result.is_interactive() {
+        let (interaction, action) = match await_click(ctx, &id_prefix).await {
+            Some(click) => click,
+            None => break
+        };
+        // Acknowledge right away, a new lookup may take longer than Discord waits for a response
+        interaction.create_interaction_response(ctx, |r| r.kind(InteractionResponseType::DeferredUpdateMessage)).await?;
+
+        let had_timeline = result.timeline().is_some();
+        result = match (result, action.as_str()) {
+            (WhoisResult::Found { response, rivals, timeline, page }, PREVIOUS_PAGE) => WhoisResult::Found { response, rivals, timeline, page: page.saturating_sub(1) },
+            (WhoisResult::Found { response, rivals, timeline, page }, NEXT_PAGE) => {
+                let last_page = response.page_count() - 1;
+                WhoisResult::Found { response, rivals, timeline, page: (page + 1).min(last_page) }
+            },
+            (WhoisResult::NotFound { cmdr, suggestions }, action) => match chosen_suggestion(action, &suggestions) {
+                Some(name) => WhoisResult::lookup(&ctx, CmdrName::new(&name)).await,
+                None => WhoisResult::NotFound { cmdr, suggestions }
+            },
+            (result, _) => result
+        };
+
+        let mut components = CreateComponents::default();
+        result.add_buttons(&mut components, &id_prefix, true);
+        interaction.edit_original_interaction_response(ctx, |r| r.set_embed(result.to_embed(&callee, layout)).set_components(components)).await?;
+        // A suggested CMDR was looked up. The interaction response can not carry files, so the timeline is added to the message afterwards.
+        if let (false, Some(timeline)) = (had_timeline, result.timeline()) {
+            add_attachment(ctx, &reply, AttachmentType::Bytes { data: Cow::Borrowed(timeline), filename: TIMELINE_FILE.to_string() }).await?;
+        }
+    }
+
+    let mut components = CreateComponents::default();
+    result.add_buttons(&mut components, &id_prefix, false);
+    if let Err(reason) = set_components(ctx, &reply, components).await {
+        println!("Error disabling the buttons of /pvpwhois: {:?}", reason)
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude::CreateEmbed;
+
+    use crate::{data_server::models::{CmdrWhoisLookupResponseSuccess, HistoryEntryJson}, other::rivals::{Rival, Rivals}, util::text::FIELD_VALUE_LIMIT};
+
+    use super::{WhoisLayout, HISTORY_PAGE_SIZE};
+
+    fn response(kills: usize, other_cmdr: &str) -> CmdrWhoisLookupResponseSuccess {
+        let recent_history = (0..kills).map(|i| HistoryEntryJson {
+            timestamp: format!("2023-12-14 12:{:02}:00", i % 60),
+            killer_name: "WDX".to_string(),
+            killer_ship: "Mamba".to_string(),
+            killer_rank: "8".to_string(),
+            victim_name: other_cmdr.to_string(),
+            victim_ship: "Python".to_string(),
+            victim_rank: "3".to_string(),
+            location: Some("Shinrarta Dezhra".to_string())
+        }).collect();
+        return CmdrWhoisLookupResponseSuccess { cmdr_name: "WDX".to_string(), kills: kills as u32, deaths: 0, recent_history };
+    }
+
+    fn field_values(embed: &CreateEmbed) -> Vec<String> {
+        let fields = embed.0.get("fields").and_then(|x| x.as_array()).cloned().unwrap_or_default();
+        return fields.iter().map(|x| x["value"].as_str().unwrap().to_string()).collect();
+    }
+
+    #[test]
+    fn history_is_paginated() {
+        let response = response(HISTORY_PAGE_SIZE * 2 + 3, "Victim");
+        assert_eq!(response.page_count(), 3);
+
+        let mut first_page = CreateEmbed::default();
+        response.apply_to_embed(&mut first_page, "callee", 0, WhoisLayout::Compact, None);
+        assert_eq!(field_values(&first_page)[0].lines().count(), HISTORY_PAGE_SIZE);
+
+        let mut last_page = CreateEmbed::default();
+        response.apply_to_embed(&mut last_page, "callee", 2, WhoisLayout::Compact, None);
+        assert_eq!(field_values(&last_page)[0].lines().count(), 3);
+
+        let mut detailed_page = CreateEmbed::default();
+        response.apply_to_embed(&mut detailed_page, "callee", 2, WhoisLayout::Detailed, None);
+        assert_eq!(field_values(&detailed_page).len(), 3);
+    }
+
+    #[test]
+    fn a_full_page_of_long_names_fits() {
+        let response = response(HISTORY_PAGE_SIZE, &"N".repeat(500));
+        let mut embed = CreateEmbed::default();
+        response.apply_to_embed(&mut embed, "callee", 0, WhoisLayout::Compact, None);
+        for value in field_values(&embed) {
+            assert!(value.chars().count() <= FIELD_VALUE_LIMIT);
+            assert_eq!(value.lines().count(), HISTORY_PAGE_SIZE);
+        }
+    }
+
+    #[test]
+    fn ships_and_ranks_of_both_sides() {
+        let mut embed = CreateEmbed::default();
+        response(1, "Victim").apply_to_embed(&mut embed, "callee", 0, WhoisLayout::Detailed, None);
+        assert_eq!(field_values(&embed), vec!["Flying a Mamba (Elite) against a Python (Competent)\n:ringed_planet: Shinrarta Dezhra"]);
+    }
+
+    #[test]
+    fn rivals_are_shown_above_the_history() {
+        let rivals = Rivals {
+            nemesis: Some(Rival { cmdr: "Nemesis".to_string(), kills: 1, deaths: 4 }),
+            favourite_target: None
+        };
+        let mut embed = CreateEmbed::default();
+        response(1, "Victim").apply_to_embed(&mut embed, "callee", 0, WhoisLayout::Compact, Some(&rivals));
+        let description = embed.0.get("description").unwrap().as_str().unwrap().to_string();
+        assert!(description.contains("**__Rivals__**\n:skull: Nemesis: **Nemesis**, killed them 4× (head-to-head 1–4)"));
+        assert!(!description.contains("Favourite target"));
+        assert!(description.find("Rivals").unwrap() < description.find("Recent History").unwrap());
+    }
+
+    #[test]
+    fn no_history_is_a_single_page() {
+        assert_eq!(response(0, "Victim").page_count(), 1);
+    }
+}
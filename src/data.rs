@@ -1,5 +1,9 @@
 
+use std::{time::Duration, net::SocketAddr};
+
+use chrono::Weekday;
 use once_cell::sync::OnceCell;
+use reqwest::StatusCode;
 use tokio_cron_scheduler::Job;
 
 
@@ -13,7 +17,30 @@ pub struct Environment {
     role_authenticated: u64,
     role_administrator: u64,
     discord_weekly_update_channel_id: u64,
-    weekly_summary_cron: String
+    weekly_summary_cron: String,
+    leaderboard_anchor_weekday: Weekday,
+    leaderboard_anchor_hour: u32,
+    data_server_timeout_seconds: u64,
+    data_server_connect_timeout_seconds: u64,
+    retry_attempts: u64,
+    retry_base_delay_millis: u64,
+    retry_max_delay_millis: u64,
+    retry_jitter_millis: u64,
+    retry_status_codes: Vec<StatusCode>,
+    weekly_summary_retry_window_minutes: u64,
+    weekly_summary_retry_interval_seconds: u64,
+    discord_admin_channel_id: Option<u64>,
+    kill_cache_path: String,
+    kill_cache_sync_interval_seconds: u64,
+    kill_cache_backfill_days: u64,
+    kill_cache_late_upload_days: u64,
+    cmdr_links_path: String,
+    live_feed_channel_id: Option<u64>,
+    live_feed_max_kills_per_batch: u64,
+    webhook_listen_address: Option<SocketAddr>,
+    webhook_secret: Option<String>,
+    kd_min_engagements: u64,
+    chart_font_path: String
 }
 
 static ENVIRONMENT: OnceCell<Environment> = OnceCell::new();
@@ -54,16 +81,117 @@ impl Environment {
         return ENVIRONMENT.get().unwrap().weekly_summary_cron.clone()
     }
 
+    /// Weekday a leaderboard week starts and ends on.
+    pub fn leaderboard_anchor_weekday() -> Weekday {
+        return ENVIRONMENT.get().unwrap().leaderboard_anchor_weekday
+    }
+
+    /// Full hour (UTC) a leaderboard week starts and ends on.
+    pub fn leaderboard_anchor_hour() -> u32 {
+        return ENVIRONMENT.get().unwrap().leaderboard_anchor_hour
+    }
+
+    pub fn data_server_timeout() -> Duration {
+        return Duration::from_secs(ENVIRONMENT.get().unwrap().data_server_timeout_seconds)
+    }
+
+    pub fn data_server_connect_timeout() -> Duration {
+        return Duration::from_secs(ENVIRONMENT.get().unwrap().data_server_connect_timeout_seconds)
+    }
+
+    pub fn retry_attempts() -> u32 {
+        return u32::try_from(ENVIRONMENT.get().unwrap().retry_attempts).unwrap_or(u32::MAX).max(1)
+    }
+
+    pub fn retry_base_delay() -> Duration {
+        return Duration::from_millis(ENVIRONMENT.get().unwrap().retry_base_delay_millis)
+    }
+
+    pub fn retry_max_delay() -> Duration {
+        return Duration::from_millis(ENVIRONMENT.get().unwrap().retry_max_delay_millis)
+    }
+
+    pub fn retry_jitter() -> Duration {
+        return Duration::from_millis(ENVIRONMENT.get().unwrap().retry_jitter_millis)
+    }
+
+    pub fn retry_status_codes() -> Vec<StatusCode> {
+        return ENVIRONMENT.get().unwrap().retry_status_codes.clone()
+    }
+
+    pub fn weekly_summary_retry_window() -> Duration {
+        return Duration::from_secs(ENVIRONMENT.get().unwrap().weekly_summary_retry_window_minutes * 60)
+    }
+
+    pub fn weekly_summary_retry_interval() -> Duration {
+        return Duration::from_secs(ENVIRONMENT.get().unwrap().weekly_summary_retry_interval_seconds)
+    }
+
+    /// Channel for messages only admins should see. Falls back to the weekly update channel.
+    pub fn discord_admin_channel_id() -> u64 {
+        let env = ENVIRONMENT.get().unwrap();
+        return env.discord_admin_channel_id.unwrap_or(env.discord_weekly_update_channel_id)
+    }
+
+    pub fn kill_cache_path() -> String {
+        return ENVIRONMENT.get().unwrap().kill_cache_path.clone()
+    }
+
+    pub fn kill_cache_sync_interval() -> Duration {
+        return Duration::from_secs(ENVIRONMENT.get().unwrap().kill_cache_sync_interval_seconds)
+    }
+
+    pub fn kill_cache_backfill_days() -> u64 {
+        return ENVIRONMENT.get().unwrap().kill_cache_backfill_days
+    }
+
+    /// How many days before now every sync downloads again, to pick up Journals that were uploaded late.
+    pub fn kill_cache_late_upload_days() -> u64 {
+        return ENVIRONMENT.get().unwrap().kill_cache_late_upload_days
+    }
+
+    pub fn cmdr_links_path() -> String {
+        return ENVIRONMENT.get().unwrap().cmdr_links_path.clone()
+    }
+
+    /// TrueType font the charts are labelled with.
+    pub fn chart_font_path() -> String {
+        return ENVIRONMENT.get().unwrap().chart_font_path.clone()
+    }
+
+    /// Channel every new kill is posted to. `None` disables the live feed.
+    pub fn live_feed_channel_id() -> Option<u64> {
+        return ENVIRONMENT.get().unwrap().live_feed_channel_id
+    }
+
+    pub fn live_feed_max_kills_per_batch() -> usize {
+        return usize::try_from(ENVIRONMENT.get().unwrap().live_feed_max_kills_per_batch).unwrap_or(usize::MAX)
+    }
+
+    /// Address the webhook listener binds to. `None` disables the webhook.
+    pub fn webhook_listen_address() -> Option<SocketAddr> {
+        return ENVIRONMENT.get().unwrap().webhook_listen_address
+    }
+
+    /// Kills and deaths a CMDR needs combined to show up on the K/D leaderboard.
+    pub fn kd_min_engagements() -> u32 {
+        return u32::try_from(ENVIRONMENT.get().unwrap().kd_min_engagements).unwrap_or(u32::MAX)
+    }
+
+    pub fn webhook_secret() -> Option<String> {
+        return ENVIRONMENT.get().unwrap().webhook_secret.clone()
+    }
+
 }
 
 
 pub fn startup_check() -> Result<(), String> {
     
-    if let Err(_) = dotenv::dotenv() {
+    if dotenv::dotenv().is_err() {
         println!("[INFO] No .env found. No Environment Variables were read.")
     }
 
-    let required_vars = vec![
+    let required_vars = [
         "DISCORD_TOKEN",
         "GGI_GUILD_ID",
         "DATA_SERVER_ADDRESS",
@@ -74,13 +202,13 @@ pub fn startup_check() -> Result<(), String> {
     ];
     let missing_vars: Vec<String> = required_vars.iter().filter(|x| std::env::var(x).is_err()).map(|x|x.to_string()).collect();
 
-    if missing_vars.len() > 0 {
+    if !missing_vars.is_empty() {
         let joined_string = missing_vars.join(",");
         return Err(format!("Cannot starts. The following environment variables must be set: [{}]", joined_string));
     }
 
     // These vars must be an Integer value
-    let integer_vars = vec![
+    let integer_vars = [
         "GGI_GUILD_ID",
         "ROLE_AUTHENTICATED",
         "ROLE_ADMINISTRATOR",
@@ -92,15 +220,31 @@ pub fn startup_check() -> Result<(), String> {
         return as_u64_result.is_err();
     }).map(|x|x.to_string()).collect();
 
-    if non_integer_vars.len() > 0 {
+    if !non_integer_vars.is_empty() {
         let joined_string = non_integer_vars.join(",");
         return Err(format!("Cannot start. The following environ variables cannot be turned into integers: [{}]", joined_string));
     }
 
-    // Get optional override cron job notation for the Weekly Post
+    let kd_min_engagements = get_optional_u64_var("LEADERBOARD_KD_MIN_ENGAGEMENTS", 5);
+
+    // Start and End of a leaderboard week
+    let leaderboard_anchor_weekday: Weekday = match std::env::var("LEADERBOARD_ANCHOR_WEEKDAY") {
+        Ok(val) if !val.trim().is_empty() => match val.trim().parse() {
+            Ok(weekday) => weekday,
+            Err(_) => return Err(format!("Cannot start. LEADERBOARD_ANCHOR_WEEKDAY must be a weekday like Thu, got {}", val))
+        },
+        _ => Weekday::Thu
+    };
+    let leaderboard_anchor_hour = get_optional_u64_var("LEADERBOARD_ANCHOR_HOUR", 8);
+    if leaderboard_anchor_hour > 23 {
+        return Err(format!("Cannot start. LEADERBOARD_ANCHOR_HOUR must be between 0 and 23, got {}", leaderboard_anchor_hour));
+    }
+    let leaderboard_anchor_hour = leaderboard_anchor_hour as u32;
+
+    // Get optional override cron job notation for the Weekly Post. By default it is posted when a leaderboard week ends.
     let weekly_summary_cron = match std::env::var("WEEKLY_SUMMARY_CRON_OVERRIDE") {
         Err(err) => match err {
-            std::env::VarError::NotPresent => "0 0 8 * * Thu".to_owned(),
+            std::env::VarError::NotPresent => format!("0 0 {} * * {}", leaderboard_anchor_hour, leaderboard_anchor_weekday),
             _ => panic!("Failed to parse WEEKLY_SUMMARY_CRON_OVERRIDE"),
         },
         Ok(val) => {
@@ -113,6 +257,63 @@ pub fn startup_check() -> Result<(), String> {
         }
     };
 
+    let data_server_timeout_seconds = get_optional_u64_var("DATA_SERVER_TIMEOUT_SECONDS", 30);
+    let data_server_connect_timeout_seconds = get_optional_u64_var("DATA_SERVER_CONNECT_TIMEOUT_SECONDS", 10);
+    let retry_attempts = get_optional_u64_var("DATA_SERVER_RETRY_ATTEMPTS", 3);
+    let retry_base_delay_millis = get_optional_u64_var("DATA_SERVER_RETRY_BASE_DELAY_MS", 500);
+    let retry_max_delay_millis = get_optional_u64_var("DATA_SERVER_RETRY_MAX_DELAY_MS", 10_000);
+    let retry_jitter_millis = get_optional_u64_var("DATA_SERVER_RETRY_JITTER_MS", 250);
+    let weekly_summary_retry_window_minutes = get_optional_u64_var("WEEKLY_SUMMARY_RETRY_WINDOW_MINUTES", 60);
+    let weekly_summary_retry_interval_seconds = get_optional_u64_var("WEEKLY_SUMMARY_RETRY_INTERVAL_SECONDS", 300);
+
+    let retry_status_codes: Vec<StatusCode> = match std::env::var("DATA_SERVER_RETRY_STATUS_CODES") {
+        Err(std::env::VarError::NotPresent) => vec![
+            StatusCode::TOO_MANY_REQUESTS,
+            StatusCode::INTERNAL_SERVER_ERROR,
+            StatusCode::BAD_GATEWAY,
+            StatusCode::SERVICE_UNAVAILABLE,
+            StatusCode::GATEWAY_TIMEOUT
+        ],
+        Err(_) => panic!("Failed to parse DATA_SERVER_RETRY_STATUS_CODES"),
+        Ok(val) => val.split(',').map(|x| x.trim()).filter(|x| !x.is_empty()).map(|x| {
+            x.parse::<u16>().ok().and_then(|x| StatusCode::from_u16(x).ok())
+                .unwrap_or_else(|| panic!("Failed to parse DATA_SERVER_RETRY_STATUS_CODES: {} is not a valid status code", x))
+        }).collect()
+    };
+
+    let kill_cache_path = match std::env::var("KILL_CACHE_PATH") {
+        Ok(val) if !val.trim().is_empty() => val,
+        _ => "kill_cache.sqlite3".to_owned()
+    };
+    let kill_cache_sync_interval_seconds = get_optional_u64_var("KILL_CACHE_SYNC_INTERVAL_SECONDS", 300);
+    let kill_cache_backfill_days = get_optional_u64_var("KILL_CACHE_BACKFILL_DAYS", 28);
+    let kill_cache_late_upload_days = get_optional_u64_var("KILL_CACHE_LATE_UPLOAD_DAYS", 2);
+
+    let cmdr_links_path = match std::env::var("CMDR_LINKS_PATH") {
+        Ok(val) if !val.trim().is_empty() => val,
+        _ => "cmdr_links.sqlite3".to_owned()
+    };
+
+    let chart_font_path = match std::env::var("CHART_FONT_PATH") {
+        Ok(val) if !val.trim().is_empty() => val,
+        _ => "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf".to_owned()
+    };
+
+    let discord_admin_channel_id = get_optional_id_var("DISCORD_ADMIN_CHANNEL_ID");
+    let live_feed_channel_id = get_optional_id_var("LIVE_FEED_CHANNEL_ID");
+    let live_feed_max_kills_per_batch = get_optional_u64_var("LIVE_FEED_MAX_KILLS_PER_BATCH", 30);
+
+    let webhook_listen_address: Option<SocketAddr> = match std::env::var("WEBHOOK_LISTEN_ADDRESS") {
+        Ok(val) if !val.trim().is_empty() => Some(val.trim().parse().unwrap_or_else(|err| panic!("Failed to parse WEBHOOK_LISTEN_ADDRESS: {}", err))),
+        _ => None
+    };
+    let webhook_secret: Option<String> = match std::env::var("WEBHOOK_SECRET") {
+        Ok(val) if !val.trim().is_empty() => Some(val),
+        _ => None
+    };
+    if webhook_listen_address.is_some() && webhook_secret.is_none() {
+        return Err("Cannot start. WEBHOOK_SECRET must be set if WEBHOOK_LISTEN_ADDRESS is set.".to_string());
+    }
 
     // After no Errors are found, Initialize the Lazy Value.
     let init_env = Environment { 
@@ -123,7 +324,30 @@ pub fn startup_check() -> Result<(), String> {
         data_server_auth: std::env::var("DATA_SERVER_AUTH").expect("missing DATA_SERVER_AUTH"), 
         role_authenticated: std::env::var("ROLE_AUTHENTICATED").expect("missing ROLE_AUTHENTICATED").parse().expect("Failed to parse ROLE_AUTHENTICATED"),
         role_administrator: std::env::var("ROLE_ADMINISTRATOR").expect("missing ROLE_ADMINISTRATOR").parse().expect("Failed to parse ROLE_ADMINISTRATOR"),
-        weekly_summary_cron: weekly_summary_cron
+        weekly_summary_cron,
+        leaderboard_anchor_weekday,
+        leaderboard_anchor_hour,
+        data_server_timeout_seconds,
+        data_server_connect_timeout_seconds,
+        retry_attempts,
+        retry_base_delay_millis,
+        retry_max_delay_millis,
+        retry_jitter_millis,
+        retry_status_codes,
+        weekly_summary_retry_window_minutes,
+        weekly_summary_retry_interval_seconds,
+        discord_admin_channel_id,
+        kill_cache_path,
+        kill_cache_sync_interval_seconds,
+        kill_cache_backfill_days,
+        kill_cache_late_upload_days,
+        cmdr_links_path,
+        live_feed_channel_id,
+        live_feed_max_kills_per_batch,
+        webhook_listen_address,
+        webhook_secret,
+        kd_min_engagements,
+        chart_font_path
     };
 
     ENVIRONMENT.set(init_env).unwrap();
@@ -131,4 +355,24 @@ pub fn startup_check() -> Result<(), String> {
     return Ok(());
 
 
-}
\ No newline at end of file
+}
+
+/// Reads an optional, positive integer environment variable. Panics if it is set but not a valid integer.
+fn get_optional_u64_var(name: &str, default: u64) -> u64 {
+    return match std::env::var(name) {
+        Err(std::env::VarError::NotPresent) => default,
+        Err(_) => panic!("Failed to parse {}", name),
+        Ok(val) if val.trim().is_empty() => default,
+        Ok(val) => val.parse().unwrap_or_else(|err| panic!("Failed to parse {}: {}", name, err))
+    };
+}
+
+/// Reads an optional Discord ID. Panics if it is set but not a valid integer.
+fn get_optional_id_var(name: &str) -> Option<u64> {
+    return match std::env::var(name) {
+        Err(std::env::VarError::NotPresent) => None,
+        Err(_) => panic!("Failed to parse {}", name),
+        Ok(val) if val.trim().is_empty() => None,
+        Ok(val) => Some(val.parse().unwrap_or_else(|err| panic!("Failed to parse {}: {}", name, err)))
+    };
+}
@@ -0,0 +1,203 @@
+pub mod models;
+pub mod retry;
+
+use std::{fmt::Display, time::Duration};
+
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude::User;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+
+use crate::{data::Environment, other::weekly_summary::get_query_string_for_api_call};
+
+use self::{models::{CmdrHistoryResponse, CmdrWhoisLookupResponseSuccess, GetRangeResponse, LinkVerification, LinkVerifyResponse, RangeResponseEntry, TokenGrant, TokenResponse}, retry::RetryPolicy};
+
+#[derive(Debug)]
+pub enum DataServerError {
+    /// The Server could not be reached or the request timed out.
+    Network(String),
+    /// The Server rejected our credentials.
+    Auth(StatusCode),
+    /// The requested resource does not exist.
+    NotFound,
+    /// The Server responded with a status we did not expect.
+    UnexpectedStatus(StatusCode),
+    /// The Server responded, but the body is not what we expected.
+    Decode(String)
+}
+
+impl Display for DataServerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataServerError::Network(err) => write!(f, "Failed to reach the Data Server: {}", err),
+            DataServerError::Auth(status) => write!(f, "The Data Server rejected the Bot's credentials ({})", status.as_str()),
+            DataServerError::NotFound => write!(f, "The Data Server has no data for this request"),
+            DataServerError::UnexpectedStatus(status) => write!(f, "Server responded with a {}", status.as_str()),
+            DataServerError::Decode(err) => write!(f, "Failed to read the Data Server's response: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for DataServerError {}
+
+impl DataServerError {
+    /// The HTTP Status the Server responded with, if it responded at all.
+    pub fn status(&self) -> Option<StatusCode> {
+        return match self {
+            DataServerError::Auth(status) | DataServerError::UnexpectedStatus(status) => Some(*status),
+            DataServerError::NotFound => Some(StatusCode::NOT_FOUND),
+            DataServerError::Network(_) | DataServerError::Decode(_) => None
+        };
+    }
+}
+
+impl From<reqwest::Error> for DataServerError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_decode() {
+            return DataServerError::Decode(err.to_string());
+        }
+        return DataServerError::Network(err.to_string());
+    }
+}
+
+/// Client for the Killboard / Data Server. Cheap to clone, all clones share one connection pool.
+#[derive(Clone, Debug)]
+pub struct DataServerClient {
+    http: reqwest::Client,
+    address: String,
+    auth: String,
+    retry_policy: RetryPolicy
+}
+
+impl DataServerClient {
+
+    pub fn new(address: String, auth: String, timeout: Duration, connect_timeout: Duration, retry_policy: RetryPolicy) -> Result<Self, reqwest::Error> {
+        let http = reqwest::Client::builder()
+            .timeout(timeout)
+            .connect_timeout(connect_timeout)
+            .build()?;
+        return Ok(DataServerClient { http, address, auth, retry_policy });
+    }
+
+    pub fn from_environment() -> Result<Self, reqwest::Error> {
+        return DataServerClient::new(
+            Environment::server_address(),
+            Environment::server_auth(),
+            Environment::data_server_timeout(),
+            Environment::data_server_connect_timeout(),
+            RetryPolicy::from_environment()
+        );
+    }
+
+    /// Sends the request, retrying it according to the [RetryPolicy]. Only meant for idempotent requests.
+    /// `build_request` is called again for every attempt, so the request is built fresh each time.
+    async fn send(&self, build_request: impl Fn() -> RequestBuilder) -> Result<Response, DataServerError> {
+        let mut attempt = 1;
+        loop {
+            match self.send_once(build_request()).await {
+                Ok(response) => return Ok(response),
+                Err(err) => {
+                    if attempt >= self.retry_policy.max_attempts || !self.retry_policy.is_retryable(&err) {
+                        return Err(err);
+                    }
+                    let delay = self.retry_policy.delay_for_attempt(attempt);
+                    println!("[WARN] Request to Data Server failed (attempt {}/{}), retrying in {}ms: {}", attempt, self.retry_policy.max_attempts, delay.as_millis(), err);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Sends the request once and maps all non-success status codes into a [DataServerError].
+    async fn send_once(&self, request: RequestBuilder) -> Result<Response, DataServerError> {
+        let response = request.bearer_auth(&self.auth).send().await?;
+        let status = response.status();
+
+        if status.is_success() {
+            return Ok(response);
+        }
+        return Err(match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => DataServerError::Auth(status),
+            StatusCode::NOT_FOUND => DataServerError::NotFound,
+            _ => DataServerError::UnexpectedStatus(status)
+        });
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, path: String) -> Result<T, DataServerError> {
+        let url = format!("{}{}", self.address, path);
+        let response = self.send(|| self.http.get(&url)).await?;
+        return response.json::<T>().await.map_err(|x| DataServerError::Decode(x.to_string()));
+    }
+
+    /// `POST /api/token/discord` – Creates a new API Token for the given Discord User.
+    /// Sent only once: a retry after a lost response would issue a second Token.
+    pub async fn request_discord_token(&self, user: &User) -> Result<TokenGrant, DataServerError> {
+        let url = format!("{}/api/token/discord", self.address);
+        let request_form = reqwest::multipart::Form::new()
+            .text("discord_name", user.tag())
+            .text("discord_id", user.id.to_string());
+
+        let response: TokenResponse = self.send_once(self.http.post(&url).multipart(request_form)).await?
+            .json().await
+            .map_err(|x| DataServerError::Decode(x.to_string()))?;
+
+        if let Some(true) = response.is_banned {
+            return Ok(TokenGrant::Banned);
+        }
+
+        return match response.token {
+            Some(token) => Ok(TokenGrant::Token(token)),
+            None => Err(DataServerError::Decode(response.error.unwrap_or_else(|| "No Token was returned".to_string())))
+        };
+    }
+
+    /// `GET /api/bot/user/{cmdr}` – Returns `None` if the Server does not know the CMDR.
+    pub async fn get_cmdr(&self, cmdr_name: &str) -> Result<Option<CmdrWhoisLookupResponseSuccess>, DataServerError> {
+        let safe_cmdr_name = urlencoding::encode(cmdr_name).into_owned();
+
+        return match self.get_json(format!("/api/bot/user/{}", safe_cmdr_name)).await {
+            Ok(data) => Ok(Some(data)),
+            Err(DataServerError::NotFound) => Ok(None),
+            Err(err) => Err(err)
+        };
+    }
+
+    /// `GET /api/bot/user/{cmdr}/history` – The complete history of the CMDR. Returns `None` if the Server does not know the CMDR.
+    /// Only the mock_server implements it so far, the Data Server has to add it for `/pvpshipstats`, `/pvpversus` and the rivals and timeline of `/pvpwhois`.
+    pub async fn get_cmdr_history(&self, cmdr_name: &str) -> Result<Option<CmdrHistoryResponse>, DataServerError> {
+        let safe_cmdr_name = urlencoding::encode(cmdr_name).into_owned();
+
+        return match self.get_json(format!("/api/bot/user/{}/history", safe_cmdr_name)).await {
+            Ok(data) => Ok(Some(data)),
+            Err(DataServerError::NotFound) => Ok(None),
+            Err(err) => Err(err)
+        };
+    }
+
+    /// `GET /api/bot/link/verify` – Asks whether the Discord User has uploaded a Journal of the CMDR using one of their API Tokens.
+    /// Only the mock_server implements it so far, the Data Server has to add it for `/pvplink` to work.
+    pub async fn verify_cmdr_link(&self, discord_id: u64, cmdr_name: &str) -> Result<LinkVerification, DataServerError> {
+        let path = format!("/api/bot/link/verify?discord_id={}&cmdr={}", discord_id, urlencoding::encode(cmdr_name));
+
+        let response: LinkVerifyResponse = match self.get_json(path).await {
+            Ok(response) => response,
+            Err(DataServerError::NotFound) => return Ok(LinkVerification::NotVerified),
+            Err(err) => return Err(err)
+        };
+
+        return match response.verified {
+            true => Ok(LinkVerification::Verified { cmdr_name: response.cmdr_name }),
+            false => Ok(LinkVerification::NotVerified)
+        };
+    }
+
+    /// `GET /killboard/get/kills` – All kills between the days of `start` and `end`.
+    /// As the API only works on whole days, the result may contain kills outside of the exact range.
+    pub async fn get_kills(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<RangeResponseEntry>, DataServerError> {
+        let query_string = get_query_string_for_api_call(start, end);
+        let response: GetRangeResponse = self.get_json(format!("/killboard/get/kills{}", query_string)).await?;
+
+        return Ok(response.kills.into_iter().map(|x| x.convert()).collect());
+    }
+}
@@ -0,0 +1,132 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+
+/// Response of `/api/token/discord`
+#[derive(Serialize, Deserialize, Debug)]
+pub(super) struct TokenResponse {
+    pub error: Option<String>,
+    #[serde(rename="isBanned")]
+    pub is_banned: Option<bool>,
+    pub token: Option<String>
+}
+
+/// What the Data Server decided when asked for a new API Token.
+pub enum TokenGrant {
+    Token(String),
+    Banned
+}
+
+/// Response of `/api/bot/link/verify`
+#[derive(Serialize, Deserialize, Debug)]
+pub(super) struct LinkVerifyResponse {
+    pub verified: bool,
+    /// Spelling of the CMDR name in the uploaded Journal
+    #[serde(rename="cmdrName")]
+    pub cmdr_name: Option<String>
+}
+
+/// Whether a Discord User has proven to play a CMDR.
+pub enum LinkVerification {
+    /// A Journal of the CMDR was uploaded with an API Token of the Discord User.
+    Verified { cmdr_name: Option<String> },
+    NotVerified
+}
+
+/// Response of `/killboard/get/kills`
+#[derive(Serialize, Debug, Deserialize)]
+pub(super) struct GetRangeResponse {
+    pub success: String,
+    pub count: u32,
+    pub kills: Vec<_RangeResponseEntry>
+}
+
+/// A single Kill as it is sent over the wire.
+#[derive(Serialize, Debug, Deserialize)]
+pub struct _RangeResponseEntry {
+    pub id: u64,
+    pub timestamp: String,
+    pub killer_name: String,
+    pub victim_name: String,
+    pub location: String,
+    /// Journal symbols of the ships. Only some sources send them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub killer_ship: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub victim_ship: Option<String>
+}
+
+#[derive(Serialize, Debug, Deserialize)]
+pub struct RangeResponseEntry {
+    pub id: u64,
+    pub timestamp: DateTime<Utc>,
+    pub killer_name: String,
+    pub victim_name: String,
+    pub location: Option<String>,
+    pub killer_ship: Option<String>,
+    pub victim_ship: Option<String>
+}
+
+impl _RangeResponseEntry {
+    /// Timestamps are sent as `YYYY-MM-DD HH:MM:SS` in UTC.
+    pub fn parse_timestamp(&self) -> Result<DateTime<Utc>, chrono::ParseError> {
+        return DateTime::parse_from_str(format!("{} +00:00", &self.timestamp).as_str(), "%Y-%m-%d %H:%M:%S %z").map(|x| x.with_timezone(&Utc));
+    }
+
+    pub fn convert(self) -> RangeResponseEntry {
+
+        let id = self.id;
+        let timestamp: DateTime<Utc> = match self.parse_timestamp() {
+            Ok(data) => data,
+            Err(err) => {
+                println!("[ERROR]: Failed to parse Timestamp. Using Utc.now as fallback. Error below:\n{err}");
+                Utc::now()
+            },
+        };
+        let location: Option<String> = match self.location.eq_ignore_ascii_case("UNKNOWN") {
+            true => None,
+            false => Some(self.location)
+        };
+        let killer_name = self.killer_name;
+        let victim_name = self.victim_name;
+        return RangeResponseEntry {
+            id,
+            timestamp,
+            killer_name,
+            victim_name,
+            location,
+            killer_ship: self.killer_ship,
+            victim_ship: self.victim_ship
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HistoryEntryJson {
+    pub timestamp: String,
+    pub killer_name: String,
+    pub killer_ship: String,
+    pub killer_rank: String,
+    pub victim_name: String,
+    pub victim_ship: String,
+    pub victim_rank: String,
+    pub location: Option<String>
+}
+
+/// Response of `/api/bot/user/{cmdr}`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CmdrWhoisLookupResponseSuccess {
+    #[serde(rename="cmdrName")]
+    pub cmdr_name: String,
+    pub kills: u32,
+    pub deaths: u32,
+    #[serde(rename="recentHistory")]
+    pub recent_history: Vec<HistoryEntryJson>
+}
+
+/// Response of `/api/bot/user/{cmdr}/history`. Unlike the whois lookup, this is every recorded engagement of the CMDR.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CmdrHistoryResponse {
+    #[serde(rename="cmdrName")]
+    pub cmdr_name: String,
+    pub history: Vec<HistoryEntryJson>
+}
@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use rand::Rng;
+use reqwest::StatusCode;
+
+use crate::data::Environment;
+
+use super::DataServerError;
+
+/// Decides if and when a failed request to the Data Server is repeated.
+/// The delay grows exponentially (`base_delay * 2^(attempt-1)`), is capped at `max_delay`
+/// and gets a random jitter of up to `jitter` added so that parallel requests don't retry in lockstep.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: Duration,
+    pub retryable_status_codes: Vec<StatusCode>
+}
+
+impl RetryPolicy {
+
+    pub fn from_environment() -> Self {
+        return RetryPolicy {
+            max_attempts: Environment::retry_attempts(),
+            base_delay: Environment::retry_base_delay(),
+            max_delay: Environment::retry_max_delay(),
+            jitter: Environment::retry_jitter(),
+            retryable_status_codes: Environment::retry_status_codes()
+        };
+    }
+
+    /// How long to wait after the given (1-based) attempt failed.
+    pub fn delay_for_attempt(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let delay = self.base_delay.saturating_mul(2u32.pow(exponent)).min(self.max_delay);
+
+        let jitter_millis = self.jitter.as_millis() as u64;
+        if jitter_millis == 0 {
+            return delay;
+        }
+        return delay + Duration::from_millis(rand::thread_rng().gen_range(0..=jitter_millis));
+    }
+
+    /// Network Errors are always retried, everything else only if the status code is configured as retryable.
+    pub fn is_retryable(&self, err: &DataServerError) -> bool {
+        if let DataServerError::Network(_) = err {
+            return true;
+        }
+        return match err.status() {
+            Some(status) => self.retryable_status_codes.contains(&status),
+            None => false
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use reqwest::StatusCode;
+
+    use crate::data_server::DataServerError;
+
+    use super::RetryPolicy;
+
+    fn policy(jitter: Duration) -> RetryPolicy {
+        return RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+            jitter,
+            retryable_status_codes: vec![StatusCode::BAD_GATEWAY, StatusCode::SERVICE_UNAVAILABLE]
+        };
+    }
+
+    #[test]
+    fn delay_doubles_up_to_the_maximum() {
+        let policy = policy(Duration::ZERO);
+        let delays: Vec<u128> = (1..=6).map(|x| policy.delay_for_attempt(x).as_millis()).collect();
+        assert_eq!(delays, vec![100, 200, 400, 800, 1000, 1000]);
+        assert_eq!(policy.delay_for_attempt(0), Duration::from_millis(100));
+        assert_eq!(policy.delay_for_attempt(u32::MAX), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let with_jitter = policy(Duration::from_millis(50));
+        let without_jitter = policy(Duration::ZERO);
+        for attempt in 1..=6 {
+            let base = without_jitter.delay_for_attempt(attempt);
+            for _ in 0..20 {
+                let delay = with_jitter.delay_for_attempt(attempt);
+                assert!(delay >= base && delay <= base + Duration::from_millis(50), "{:?} for attempt {}", delay, attempt);
+            }
+        }
+    }
+
+    #[test]
+    fn retries_network_errors_and_configured_status_codes() {
+        let policy = policy(Duration::ZERO);
+        assert!(policy.is_retryable(&DataServerError::Network("timed out".to_string())));
+        assert!(policy.is_retryable(&DataServerError::UnexpectedStatus(StatusCode::BAD_GATEWAY)));
+        assert!(!policy.is_retryable(&DataServerError::UnexpectedStatus(StatusCode::INTERNAL_SERVER_ERROR)));
+        assert!(!policy.is_retryable(&DataServerError::Auth(StatusCode::UNAUTHORIZED)));
+        assert!(!policy.is_retryable(&DataServerError::NotFound));
+        assert!(!policy.is_retryable(&DataServerError::Decode("missing field".to_string())));
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::util::cmdr_name::{closest_names, CmdrName, SpellingCounter};
+
+/// Discord shows at most 25 autocomplete choices.
+pub const MAX_AUTOCOMPLETE_CHOICES: usize = 25;
+
+struct IndexEntry {
+    spellings: SpellingCounter,
+    last_seen: DateTime<Utc>
+}
+
+/// Every CMDR name seen in the Kill Cache, for autocompletion.
+#[derive(Default)]
+pub struct CmdrIndex {
+    entries: HashMap<String, IndexEntry>
+}
+
+/// How well a name matches what the user typed so far. Lower is better.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum MatchQuality {
+    Exact,
+    Prefix,
+    WordPrefix,
+    Substring,
+    /// All characters of the input appear in order, e.g. "mtp" for "MARVIN THE PERPLEXED"
+    Fuzzy
+}
+
+fn match_quality(name_key: &str, input_key: &str) -> Option<MatchQuality> {
+    if name_key == input_key {
+        return Some(MatchQuality::Exact);
+    }
+    if name_key.starts_with(input_key) {
+        return Some(MatchQuality::Prefix);
+    }
+    if name_key.split_whitespace().any(|word| word.starts_with(input_key)) {
+        return Some(MatchQuality::WordPrefix);
+    }
+    if name_key.contains(input_key) {
+        return Some(MatchQuality::Substring);
+    }
+    let mut name_chars = name_key.chars();
+    let is_subsequence = input_key.chars().filter(|x| !x.is_whitespace()).all(|x| name_chars.any(|y| y == x));
+    return match is_subsequence {
+        true => Some(MatchQuality::Fuzzy),
+        false => None
+    };
+}
+
+impl CmdrIndex {
+
+    pub fn add(&mut self, name: &str, seen_at: DateTime<Utc>) {
+        let name = CmdrName::new(name);
+        if name.key().is_empty() {
+            return;
+        }
+        let entry = self.entries.entry(name.key().to_string()).or_insert_with(|| IndexEntry { spellings: SpellingCounter::default(), last_seen: seen_at });
+        entry.spellings.add(&name);
+        entry.last_seen = entry.last_seen.max(seen_at);
+    }
+
+    /// Up to `limit` names matching `input`. Better matches come first, then the CMDRs that were active most recently.
+    /// Without any input, these are simply the most recently active CMDRs.
+    pub fn search(&self, input: &str, limit: usize) -> Vec<String> {
+        let input = CmdrName::new(input);
+
+        let mut matches: Vec<(MatchQuality, &IndexEntry, &String)> = self.entries.iter()
+            .filter_map(|(key, entry)| match_quality(key, input.key()).map(|quality| (quality, entry, key)))
+            .collect();
+        matches.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| b.1.last_seen.cmp(&a.1.last_seen)).then_with(|| a.2.cmp(b.2)));
+
+        return matches.into_iter()
+            .take(limit)
+            .filter_map(|(_, entry, _)| entry.spellings.most_common())
+            .collect();
+    }
+
+    /// Up to `limit` names closest to `input` by edit distance, for when a CMDR can not be found. See [closest_names].
+    pub fn closest(&self, input: &str, limit: usize) -> Vec<String> {
+        let spellings: Vec<String> = self.entries.values().filter_map(|x| x.spellings.most_common()).collect();
+        return closest_names(input, spellings.iter().map(|x| x.as_str()), limit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::CmdrIndex;
+
+    fn index() -> CmdrIndex {
+        let mut index = CmdrIndex::default();
+        let names = [("MARVIN THE PERPLEXED", 1), ("Marvin", 5), ("phDeez", 3), ("Mar", 2), ("CMDR phdeez", 4), ("Arthur Dent", 6)];
+        for (name, day) in names {
+            index.add(name, Utc.with_ymd_and_hms(2023, 12, day, 0, 0, 0).unwrap());
+        }
+        return index;
+    }
+
+    #[test]
+    fn prefix_matches_come_first_then_recent_activity() {
+        assert_eq!(index().search("mar", 25), vec!["Mar", "Marvin", "MARVIN THE PERPLEXED"]);
+    }
+
+    #[test]
+    fn word_substring_and_fuzzy_matches() {
+        let index = index();
+        assert_eq!(index.search("perp", 25), vec!["MARVIN THE PERPLEXED"]);
+        assert_eq!(index.search("dent", 25), vec!["Arthur Dent"]);
+        assert_eq!(index.search("eez", 25), vec!["phDeez"]);
+        assert_eq!(index.search("mtp", 25), vec!["MARVIN THE PERPLEXED"]);
+        assert!(index.search("xyz", 25).is_empty());
+    }
+
+    #[test]
+    fn spellings_are_merged() {
+        let index = index();
+        // "phDeez" and "CMDR phdeez" are one CMDR, last seen on day 4
+        assert_eq!(index.search("CMDR PHD", 25).len(), 1);
+        assert_eq!(index.search("", 2), vec!["Arthur Dent", "Marvin"]);
+        assert_eq!(index.search("", 25).len(), 5);
+    }
+
+    #[test]
+    fn closest_names_for_typos() {
+        assert_eq!(index().closest("phdeze", 5), vec!["phDeez"]);
+        assert_eq!(index().closest("marvim", 5), vec!["Marvin"]);
+    }
+}
@@ -0,0 +1,334 @@
+pub mod sync;
+pub mod cmdr_index;
+
+use std::{fmt::Display, sync::{Arc, Mutex, RwLock}};
+
+use chrono::{DateTime, Utc, TimeZone};
+use rusqlite::{Connection, OptionalExtension, params};
+
+use crate::data_server::{DataServerError, models::RangeResponseEntry};
+
+use self::cmdr_index::CmdrIndex;
+
+#[derive(Debug)]
+pub enum KillCacheError {
+    Database(rusqlite::Error),
+    Upstream(DataServerError)
+}
+
+impl Display for KillCacheError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KillCacheError::Database(err) => write!(f, "Failed to access the local Kill Cache: {}", err),
+            KillCacheError::Upstream(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for KillCacheError {}
+
+impl From<rusqlite::Error> for KillCacheError {
+    fn from(err: rusqlite::Error) -> Self {
+        return KillCacheError::Database(err);
+    }
+}
+
+impl From<DataServerError> for KillCacheError {
+    fn from(err: DataServerError) -> Self {
+        return KillCacheError::Upstream(err);
+    }
+}
+
+const META_COVERED_FROM: &str = "covered_from";
+
+/// Local SQLite copy of the Killboard. Kills are keyed by their upstream `id`, so inserting the same kill again only updates it.
+/// Every CMDR name in the cache is also kept in memory, see [KillCache::search_cmdrs].
+/// Cheap to clone, all clones share the same connection and index.
+#[derive(Clone)]
+pub struct KillCache {
+    connection: Arc<Mutex<Connection>>,
+    cmdr_index: Arc<RwLock<CmdrIndex>>
+}
+
+impl KillCache {
+
+    pub fn open(path: &str) -> Result<Self, KillCacheError> {
+        let connection = Connection::open(path)?;
+        connection.execute_batch("
+            CREATE TABLE IF NOT EXISTS kills (
+                id INTEGER PRIMARY KEY,
+                timestamp INTEGER NOT NULL,
+                killer_name TEXT NOT NULL,
+                victim_name TEXT NOT NULL,
+                location TEXT,
+                killer_ship TEXT,
+                victim_ship TEXT,
+                live_feed_pending INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE INDEX IF NOT EXISTS kills_timestamp ON kills(timestamp);
+            CREATE TABLE IF NOT EXISTS meta (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+        ")?;
+        add_missing_columns(&connection)?;
+
+        let mut cmdr_index = CmdrIndex::default();
+        {
+            let mut statement = connection.prepare("SELECT timestamp, killer_name, victim_name FROM kills")?;
+            let mut rows = statement.query([])?;
+            while let Some(row) = rows.next()? {
+                let timestamp = Utc.timestamp_opt(row.get(0)?, 0).single().unwrap_or_default();
+                cmdr_index.add(&row.get::<_, String>(1)?, timestamp);
+                cmdr_index.add(&row.get::<_, String>(2)?, timestamp);
+            }
+        }
+
+        return Ok(KillCache { connection: Arc::new(Mutex::new(connection)), cmdr_index: Arc::new(RwLock::new(cmdr_index)) });
+    }
+
+    /// Inserts all kills that are not yet known and updates the ones that are, in case upstream corrected them. Returns how many were new.
+    /// Meant for filling in the past, none of the kills end up in the live feed.
+    pub fn insert_kills(&self, kills: &[RangeResponseEntry]) -> Result<usize, KillCacheError> {
+        return self.insert(kills, false);
+    }
+
+    /// Like [KillCache::insert_kills], but the new kills are also queued for the live feed, see [KillCache::take_live_feed_kills].
+    pub fn insert_live_kills(&self, kills: &[RangeResponseEntry]) -> Result<usize, KillCacheError> {
+        return self.insert(kills, true);
+    }
+
+    fn insert(&self, kills: &[RangeResponseEntry], live_feed_pending: bool) -> Result<usize, KillCacheError> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+        let mut inserted: Vec<&RangeResponseEntry> = vec![];
+        {
+            let mut insert = transaction.prepare_cached(
+                "INSERT OR IGNORE INTO kills (id, timestamp, killer_name, victim_name, location, killer_ship, victim_ship, live_feed_pending) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"
+            )?;
+            let mut update = transaction.prepare_cached(
+                "UPDATE kills SET timestamp = ?2, killer_name = ?3, victim_name = ?4, location = ?5, killer_ship = ?6, victim_ship = ?7 WHERE id = ?1"
+            )?;
+            for kill in kills {
+                if insert.execute(params![kill.id as i64, kill.timestamp.timestamp(), kill.killer_name, kill.victim_name, kill.location, kill.killer_ship, kill.victim_ship, live_feed_pending])? > 0 {
+                    inserted.push(kill);
+                } else {
+                    update.execute(params![kill.id as i64, kill.timestamp.timestamp(), kill.killer_name, kill.victim_name, kill.location, kill.killer_ship, kill.victim_ship])?;
+                }
+            }
+        }
+        transaction.commit()?;
+
+        let mut cmdr_index = self.cmdr_index.write().unwrap();
+        for kill in &inserted {
+            cmdr_index.add(&kill.killer_name, kill.timestamp);
+            cmdr_index.add(&kill.victim_name, kill.timestamp);
+        }
+        return Ok(inserted.len());
+    }
+
+    /// CMDR names matching what a user typed so far, best matches first. See [CmdrIndex::search].
+    pub fn search_cmdrs(&self, input: &str, limit: usize) -> Vec<String> {
+        return self.cmdr_index.read().unwrap().search(input, limit);
+    }
+
+    /// Known CMDR names closest to a name that could not be found. See [CmdrIndex::closest].
+    pub fn closest_cmdrs(&self, input: &str, limit: usize) -> Vec<String> {
+        return self.cmdr_index.read().unwrap().closest(input, limit);
+    }
+
+    /// All cached kills from `start` (inclusive) to `end` (exclusive), oldest first. Like [LeaderboardWindow](crate::other::leaderboard_window::LeaderboardWindow),
+    /// so a kill on the boundary of two windows only counts for the later one.
+    pub fn kills_between(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<RangeResponseEntry>, KillCacheError> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare_cached(
+            "SELECT id, timestamp, killer_name, victim_name, location, killer_ship, victim_ship FROM kills WHERE timestamp >= ?1 AND timestamp < ?2 ORDER BY timestamp, id"
+        )?;
+        let rows = statement.query_map(params![start.timestamp(), end.timestamp()], row_to_entry)?;
+        return Ok(rows.collect::<Result<Vec<_>, _>>()?);
+    }
+
+    /// All kills queued for the live feed, in the order they were logged. They are no longer queued afterwards.
+    pub fn take_live_feed_kills(&self) -> Result<Vec<RangeResponseEntry>, KillCacheError> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+        let kills = {
+            let mut statement = transaction.prepare_cached(
+                "SELECT id, timestamp, killer_name, victim_name, location, killer_ship, victim_ship FROM kills WHERE live_feed_pending = 1 ORDER BY id"
+            )?;
+            let rows = statement.query_map([], row_to_entry)?;
+            rows.collect::<Result<Vec<_>, _>>()?
+        };
+        transaction.execute("UPDATE kills SET live_feed_pending = 0 WHERE live_feed_pending = 1", [])?;
+        transaction.commit()?;
+        return Ok(kills);
+    }
+
+    /// The `limit` newest cached kills in a system, newest first. System names are compared case-insensitively.
+    pub fn kills_in_system(&self, system: &str, limit: usize) -> Result<Vec<RangeResponseEntry>, KillCacheError> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare_cached(
+            "SELECT id, timestamp, killer_name, victim_name, location, killer_ship, victim_ship FROM kills WHERE location = ?1 COLLATE NOCASE ORDER BY timestamp DESC, id DESC LIMIT ?2"
+        )?;
+        let rows = statement.query_map(params![system.trim(), limit as i64], row_to_entry)?;
+        return Ok(rows.collect::<Result<Vec<_>, _>>()?);
+    }
+
+    /// Timestamp of the newest cached kill.
+    pub fn latest_timestamp(&self) -> Result<Option<DateTime<Utc>>, KillCacheError> {
+        let connection = self.connection.lock().unwrap();
+        let latest: Option<i64> = connection.query_row("SELECT MAX(timestamp) FROM kills", [], |row| row.get(0))?;
+        return Ok(latest.and_then(|x| Utc.timestamp_opt(x, 0).single()));
+    }
+
+    /// Start of the time range the cache holds *every* kill for. `None` if the cache was never filled.
+    pub fn covered_from(&self) -> Result<Option<DateTime<Utc>>, KillCacheError> {
+        let value = self.get_meta(META_COVERED_FROM)?;
+        return Ok(value.and_then(|x| x.parse::<i64>().ok()).and_then(|x| Utc.timestamp_opt(x, 0).single()));
+    }
+
+    pub fn set_covered_from(&self, covered_from: DateTime<Utc>) -> Result<(), KillCacheError> {
+        return self.set_meta(META_COVERED_FROM, covered_from.timestamp().to_string());
+    }
+
+    pub fn get_meta(&self, key: &str) -> Result<Option<String>, KillCacheError> {
+        let connection = self.connection.lock().unwrap();
+        let value = connection.query_row("SELECT value FROM meta WHERE key = ?1", params![key], |row| row.get(0)).optional()?;
+        return Ok(value);
+    }
+
+    pub fn set_meta(&self, key: &str, value: String) -> Result<(), KillCacheError> {
+        let connection = self.connection.lock().unwrap();
+        connection.execute("INSERT INTO meta (key, value) VALUES (?1, ?2) ON CONFLICT(key) DO UPDATE SET value = excluded.value", params![key, value])?;
+        return Ok(());
+    }
+}
+
+/// Caches created before ships were stored, or before the live feed queue, lack those columns.
+fn add_missing_columns(connection: &Connection) -> Result<(), rusqlite::Error> {
+    let mut statement = connection.prepare("SELECT name FROM pragma_table_info('kills')")?;
+    let columns = statement.query_map([], |row| row.get::<_, String>(0))?.collect::<Result<Vec<_>, _>>()?;
+    for (column, definition) in [("killer_ship", "TEXT"), ("victim_ship", "TEXT"), ("live_feed_pending", "INTEGER NOT NULL DEFAULT 0")] {
+        if !columns.iter().any(|x| x == column) {
+            connection.execute(&format!("ALTER TABLE kills ADD COLUMN {} {}", column, definition), [])?;
+        }
+    }
+    return Ok(());
+}
+
+fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<RangeResponseEntry> {
+    return Ok(RangeResponseEntry {
+        id: row.get::<_, i64>(0)? as u64,
+        timestamp: Utc.timestamp_opt(row.get(1)?, 0).single().unwrap_or_default(),
+        killer_name: row.get(2)?,
+        victim_name: row.get(3)?,
+        location: row.get(4)?,
+        killer_ship: row.get(5)?,
+        victim_ship: row.get(6)?
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::{data_server::models::RangeResponseEntry, test_support::kill};
+
+    use super::KillCache;
+
+    fn at(minute: u32) -> chrono::DateTime<Utc> {
+        return Utc.with_ymd_and_hms(2023, 12, 14, 12, minute, 0).unwrap();
+    }
+
+    #[test]
+    fn known_kills_are_updated_instead_of_inserted() {
+        let cache = KillCache::open(":memory:").unwrap();
+        assert_eq!(cache.insert_kills(&[kill(1, at(0), "Alpha", "Bravo"), kill(2, at(1), "Alpha", "Charlie")]).unwrap(), 2);
+
+        let corrected = RangeResponseEntry { killer_ship: Some("mamba".to_string()), ..kill(2, at(1), "Alpha", "Charlie") };
+        assert_eq!(cache.insert_kills(&[kill(1, at(0), "Alpha", "Bravo"), corrected, kill(3, at(2), "Bravo", "Alpha")]).unwrap(), 1);
+
+        let kills = cache.kills_between(at(0), at(59)).unwrap();
+        assert_eq!(kills.iter().map(|x| x.id).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(kills[1].killer_ship.as_deref(), Some("mamba"));
+        assert_eq!(cache.latest_timestamp().unwrap(), Some(at(2)));
+        assert_eq!(cache.search_cmdrs("char", 5), vec!["Charlie".to_string()]);
+    }
+
+    #[test]
+    fn kills_between_excludes_the_end() {
+        let cache = KillCache::open(":memory:").unwrap();
+        // 2023-12-14 is a Thursday, the default start of a leaderboard week
+        let boundary = Utc.with_ymd_and_hms(2023, 12, 14, 8, 0, 0).unwrap();
+        let second = chrono::Duration::seconds(1);
+        let week = chrono::Duration::days(7);
+        cache.insert_kills(&[kill(1, boundary - week, "Alpha", "Bravo"), kill(2, boundary - second, "Alpha", "Bravo"), kill(3, boundary, "Alpha", "Bravo")]).unwrap();
+
+        let ids = |start, end| cache.kills_between(start, end).unwrap().iter().map(|x| x.id).collect::<Vec<_>>();
+        assert_eq!(ids(boundary - week, boundary), vec![1, 2]);
+        assert_eq!(ids(boundary, boundary + week), vec![3]);
+        assert_eq!(ids(boundary, boundary), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn migrates_caches_without_ship_columns() {
+        let path = std::env::temp_dir().join(format!("kill_cache_migration_{}.sqlite", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        {
+            let connection = rusqlite::Connection::open(&path).unwrap();
+            connection.execute_batch("
+                CREATE TABLE kills (
+                    id INTEGER PRIMARY KEY,
+                    timestamp INTEGER NOT NULL,
+                    killer_name TEXT NOT NULL,
+                    victim_name TEXT NOT NULL,
+                    location TEXT
+                );
+                INSERT INTO kills (id, timestamp, killer_name, victim_name, location) VALUES (1, 1702555200, 'Alpha', 'Bravo', 'Deciat');
+            ").unwrap();
+        }
+
+        // Opening twice makes sure the migration can run on an already migrated cache
+        for _ in 0..2 {
+            let cache = KillCache::open(path.to_str().unwrap()).unwrap();
+            let kills = cache.kills_between(at(0), at(59)).unwrap();
+            assert_eq!(kills.len(), 1);
+            assert_eq!((kills[0].location.as_deref(), kills[0].killer_ship.as_deref()), (Some("Deciat"), None));
+            assert!(cache.take_live_feed_kills().unwrap().is_empty());
+        }
+
+        let cache = KillCache::open(path.to_str().unwrap()).unwrap();
+        cache.insert_live_kills(&[RangeResponseEntry { killer_ship: Some("mamba".to_string()), ..kill(2, at(1), "Alpha", "Bravo") }]).unwrap();
+        assert_eq!(cache.take_live_feed_kills().unwrap()[0].killer_ship.as_deref(), Some("mamba"));
+        drop(cache);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn empty_cache() {
+        let cache = KillCache::open(":memory:").unwrap();
+        assert_eq!(cache.latest_timestamp().unwrap(), None);
+        assert_eq!(cache.covered_from().unwrap(), None);
+
+        cache.set_covered_from(at(30)).unwrap();
+        cache.set_covered_from(at(10)).unwrap();
+        assert_eq!(cache.covered_from().unwrap(), Some(at(10)));
+    }
+
+    #[test]
+    fn live_feed_gets_every_live_kill_once() {
+        let cache = KillCache::open(":memory:").unwrap();
+        cache.insert_kills(&[kill(1, at(0), "Alpha", "Bravo")]).unwrap();
+        cache.insert_live_kills(&[kill(1, at(0), "Alpha", "Bravo"), kill(5, at(5), "Alpha", "Bravo")]).unwrap();
+        // Uploaded late: a lower id than a kill that was already posted
+        let posted = cache.take_live_feed_kills().unwrap();
+        assert_eq!(posted.iter().map(|x| x.id).collect::<Vec<_>>(), vec![5]);
+
+        cache.insert_live_kills(&[kill(3, at(3), "Charlie", "Bravo"), kill(5, at(5), "Alpha", "Bravo")]).unwrap();
+        // Backfilled history is never posted
+        cache.insert_kills(&[kill(2, at(2), "Charlie", "Bravo")]).unwrap();
+        let posted = cache.take_live_feed_kills().unwrap();
+        assert_eq!(posted.iter().map(|x| x.id).collect::<Vec<_>>(), vec![3]);
+        assert!(cache.take_live_feed_kills().unwrap().is_empty());
+    }
+}
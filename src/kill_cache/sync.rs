@@ -0,0 +1,118 @@
+use std::ops::Sub;
+
+use chrono::{DateTime, Utc, Days};
+
+use crate::{data::Environment, data_server::{DataServerClient, models::RangeResponseEntry}, other::live_feed::publish_new_kills};
+
+use super::{KillCache, KillCacheError};
+
+/// Fetches all kills since the newest cached kill. Journals can be uploaded after the kills happened, so the last
+/// [Environment::kill_cache_late_upload_days] are requested again as well and upserted by id.
+/// A cache that was never synced is backfilled for the configured amount of days.
+pub async fn sync_once(cache: &KillCache, client: &DataServerClient) -> Result<usize, KillCacheError> {
+    let now = Utc::now();
+    let covered_from = cache.covered_from()?;
+    let start = sync_start(
+        now,
+        covered_from,
+        cache.latest_timestamp()?,
+        Days::new(Environment::kill_cache_backfill_days()),
+        Days::new(Environment::kill_cache_late_upload_days())
+    );
+
+    let kills = client.get_kills(start, now).await?;
+    // The backfill of an empty cache is history, not news for the live feed
+    let inserted = match covered_from {
+        None => cache.insert_kills(&kills)?,
+        Some(_) => cache.insert_live_kills(&kills)?
+    };
+
+    if covered_from.is_none() {
+        cache.set_covered_from(start)?;
+    }
+
+    return Ok(inserted);
+}
+
+/// Where [sync_once] starts fetching.
+fn sync_start(now: DateTime<Utc>, covered_from: Option<DateTime<Utc>>, latest: Option<DateTime<Utc>>, backfill: Days, late_upload: Days) -> DateTime<Utc> {
+    // Kills pushed by the webhook may already be cached, so only the coverage tells whether this is the first sync
+    let Some(covered_from) = covered_from else {
+        return now.sub(backfill);
+    };
+    return latest.unwrap_or(covered_from).min(now.sub(late_upload));
+}
+
+/// Keeps the cache up to date for as long as the Bot runs. New kills are handed to the live feed after every sync.
+pub fn spawn_background_sync(cache: KillCache, client: DataServerClient) {
+    let interval = Environment::kill_cache_sync_interval();
+    tokio::spawn(async move {
+        loop {
+            match sync_once(&cache, &client).await {
+                Ok(0) => {},
+                Ok(inserted) => println!("[INFO] Kill Cache synced, {} new kills", inserted),
+                Err(err) => println!("[WARN] Failed to sync Kill Cache: {}", err),
+            }
+            if let Err(err) = publish_new_kills(&cache).await {
+                println!("[WARN] Failed to publish new kills to the live feed: {}", err);
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+/// All kills from `start` (inclusive) to `end` (exclusive), read from the cache.
+/// If the range reaches further back than the cache, the missing part is fetched from the Data Server first.
+pub async fn get_kills_between(cache: &KillCache, client: &DataServerClient, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<RangeResponseEntry>, KillCacheError> {
+    let covered_from = cache.covered_from()?;
+    if let Some((missing_start, missing_end)) = missing_range(covered_from, start, end) {
+        let kills = client.get_kills(missing_start, missing_end).await?;
+        cache.insert_kills(&kills)?;
+        // Nothing synced yet means there is no coverage that could be extended
+        if covered_from.is_some() {
+            cache.set_covered_from(missing_start)?;
+        }
+    }
+
+    return cache.kills_between(start, end);
+}
+
+/// The part of `start` to `end` that has to be fetched from the Data Server before the cache can answer it, if any.
+fn missing_range(covered_from: Option<DateTime<Utc>>, start: DateTime<Utc>, end: DateTime<Utc>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    return match covered_from {
+        None => Some((start, end)),
+        Some(covered_from) if start < covered_from => Some((start, covered_from)),
+        Some(_) => None
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use chrono::Days;
+
+    use super::{missing_range, sync_start};
+
+    #[test]
+    fn sync_starts_at_the_backfill_or_the_late_upload_margin() {
+        let at = |day: u32| Utc.with_ymd_and_hms(2023, 12, day, 8, 0, 0).unwrap();
+        let start = |covered_from, latest| sync_start(at(28), covered_from, latest, Days::new(21), Days::new(2));
+        assert_eq!(start(None, None), at(7));
+        // A kill pushed by the webhook before the first sync does not shorten the backfill
+        assert_eq!(start(None, Some(at(27))), at(7));
+        assert_eq!(start(Some(at(7)), Some(at(28))), at(26));
+        // A cache that fell behind catches up from its newest kill
+        assert_eq!(start(Some(at(7)), Some(at(20))), at(20));
+        assert_eq!(start(Some(at(7)), None), at(7));
+    }
+
+    #[test]
+    fn fetches_only_what_the_cache_does_not_cover() {
+        let at = |day: u32| Utc.with_ymd_and_hms(2023, 12, day, 8, 0, 0).unwrap();
+        assert_eq!(missing_range(None, at(1), at(8)), Some((at(1), at(8))));
+        assert_eq!(missing_range(Some(at(5)), at(1), at(8)), Some((at(1), at(5))));
+        assert_eq!(missing_range(Some(at(5)), at(5), at(8)), None);
+        assert_eq!(missing_range(Some(at(1)), at(7), at(14)), None);
+    }
+}
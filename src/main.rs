@@ -1,15 +1,27 @@
 mod commands;
 mod data;
+mod data_server;
+mod kill_cache;
+mod cmdr_links;
+mod charts;
+mod webhook;
 mod util;
 mod other;
-use commands::{pvpself::pvpself, pvpwhois::pvpwhois, pvpregister::pvpregister, pvpweekly::pvpweekly};
+#[cfg(test)]
+pub(crate) mod test_support;
+use commands::{pvpself::pvpself, pvpwhois::pvpwhois, pvpregister::pvpregister, pvpweekly::pvpweekly, pvpleaderboard::pvpleaderboard, pvplink::pvplink, pvpshipstats::pvpshipstats, pvpversus::pvpversus, pvphotspots::pvphotspots, pvpsystem::pvpsystem, pvpactivity::pvpactivity, pvptrends::pvptrends};
 use poise::serenity_prelude as serenity;
 
-use crate::{other::weekly_summary_event::subscribe_for_event, commands::admin::pvpadminforceweeklysummary::pvpadmin_force_weekly_summary, data::Environment};
+use crate::{other::weekly_summary_event::run_scheduled_summary, commands::admin::pvpadminforceweeklysummary::pvpadmin_force_weekly_summary, data::Environment, data_server::DataServerClient, kill_cache::{KillCache, sync::spawn_background_sync}, cmdr_links::CmdrLinks, webhook::spawn_webhook_listener};
 use tokio_cron_scheduler::{JobScheduler, Job};
 
 
-pub struct Data {}
+#[derive(Clone)]
+pub struct Data {
+    pub data_server: DataServerClient,
+    pub kill_cache: KillCache,
+    pub cmdr_links: CmdrLinks
+}
 pub type Error = Box<dyn std::error::Error + Send + Sync>;
 pub type Context<'a> = poise::Context<'a, Data, Error>;
 
@@ -19,9 +31,14 @@ pub type Context<'a> = poise::Context<'a, Data, Error>;
 async fn main() {
     // This will panic and fail is there is an Error Return value.
     data::startup_check().unwrap();
+    let data_server = DataServerClient::from_environment().expect("Failed to create Data Server Client");
+    let kill_cache = KillCache::open(&Environment::kill_cache_path()).expect("Failed to open Kill Cache");
+    let cmdr_links = CmdrLinks::open(&Environment::cmdr_links_path()).expect("Failed to open CMDR Links");
+    let bot_data = Data { data_server, kill_cache, cmdr_links };
+    let framework_bot_data = bot_data.clone();
     let framework = poise::Framework::builder()
         .options(poise::FrameworkOptions {
-            commands: vec![pvpself(), pvpwhois(), pvpregister(), pvpweekly(), pvpadmin_force_weekly_summary()],
+            commands: vec![pvpself(), pvpwhois(), pvpregister(), pvpweekly(), pvpleaderboard(), pvplink(), pvpshipstats(), pvpversus(), pvphotspots(), pvpsystem(), pvpactivity(), pvptrends(), pvpadmin_force_weekly_summary()],
             ..Default::default()
         })
         .token(data::Environment::discord_token())
@@ -30,7 +47,7 @@ async fn main() {
             Box::pin(async move {
                 let guild_id = serenity::GuildId(data::Environment::guild_id());
                 poise::builtins::register_in_guild(ctx, &framework.options().commands, guild_id).await?;
-                Ok(Data {})
+                Ok(framework_bot_data)
             })
         });
     
@@ -38,9 +55,11 @@ async fn main() {
     println!("Setting up Cron Scheduler...");
     let sched = JobScheduler::new().await.unwrap();
 
-    let job = Job::new_async(Environment::weekly_summary_cron().as_str(), | _uuid, mut _l| {
+    let job_bot_data = bot_data.clone();
+    let job = Job::new_async(Environment::weekly_summary_cron().as_str(), move | _uuid, mut _l| {
+        let bot_data = job_bot_data.clone();
         Box::pin(async move {
-            let _ = subscribe_for_event(None).await;
+            run_scheduled_summary(&bot_data).await;
         })
     }).map_err(|x| x.to_string());   
     
@@ -50,11 +69,15 @@ async fn main() {
             val
         ).await {
             Ok(_) => println!("Scheduler setup."),
-            Err(err) => println!("{}", err.to_string()),
+            Err(err) => println!("{}", err),
         }
     }
 
     let _ = sched.start().await;
+
+    println!("Starting Kill Cache sync...");
+    spawn_background_sync(bot_data.kill_cache.clone(), bot_data.data_server.clone());
+    spawn_webhook_listener(bot_data.clone());
     
     println!("Starting up Bot...");
     framework.run().await.unwrap();
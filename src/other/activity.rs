@@ -0,0 +1,128 @@
+use chrono::{Datelike, Timelike};
+
+use crate::{data_server::models::RangeResponseEntry, util::cmdr_name::CmdrName};
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// From no activity to the busiest hour.
+const SHADES: [char; 5] = ['·', '░', '▒', '▓', '█'];
+
+/// Kills bucketed by UTC weekday and hour.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityHeatmap {
+    /// Indexed by weekday (Monday first), then hour
+    pub counts: [[u32; 24]; 7]
+}
+
+impl ActivityHeatmap {
+
+    /// Counts every kill in `data`. With a CMDR, only the kills they were the killer or the victim of.
+    pub fn from_kills(data: &[RangeResponseEntry], cmdr: Option<&CmdrName>) -> Self {
+        let mut counts = [[0; 24]; 7];
+        for kill in data {
+            if let Some(cmdr) = cmdr {
+                if !cmdr.matches(&kill.killer_name) && !cmdr.matches(&kill.victim_name) {
+                    continue;
+                }
+            }
+            let weekday = kill.timestamp.weekday().num_days_from_monday() as usize;
+            let hour = kill.timestamp.hour() as usize;
+            counts[weekday][hour] += 1;
+        }
+        return ActivityHeatmap { counts };
+    }
+
+    pub fn total(&self) -> u32 {
+        return self.counts.iter().flatten().sum();
+    }
+
+    /// Weekday name, hour and count of the busiest hour. The earliest one in the week on a tie, `None` without any activity.
+    pub fn peak(&self) -> Option<(&'static str, usize, u32)> {
+        let mut peak: Option<(&'static str, usize, u32)> = None;
+        for (weekday, hours) in self.counts.iter().enumerate() {
+            for (hour, count) in hours.iter().enumerate() {
+                if *count > peak.map(|x| x.2).unwrap_or(0) {
+                    peak = Some((WEEKDAYS[weekday], hour, *count));
+                }
+            }
+        }
+        return peak;
+    }
+
+    /// One line per weekday and one character per hour, shaded relative to the busiest hour. Meant for a code block.
+    pub fn to_text_grid(&self) -> String {
+        let max = self.counts.iter().flatten().copied().max().unwrap_or(0);
+        // Hour labels line up with the first column of their hour
+        let mut lines: Vec<String> = vec!["    0     6     12    18".to_string()];
+        for (weekday, hours) in self.counts.iter().enumerate() {
+            let row: String = hours.iter().map(|count| {
+                if *count == 0 {
+                    return SHADES[0];
+                }
+                // Any activity at all gets at least the lightest shade
+                let level = (*count as usize * (SHADES.len() - 1)).div_ceil(max as usize);
+                SHADES[level]
+            }).collect();
+            lines.push(format!("{} {}", WEEKDAYS[weekday], row));
+        }
+        return lines.join("\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::{data_server::models::RangeResponseEntry, test_support, util::cmdr_name::CmdrName};
+
+    use super::ActivityHeatmap;
+
+    fn kill(killer: &str, victim: &str, day: u32, hour: u32) -> RangeResponseEntry {
+        // 2023-12-11 is a Monday
+        return test_support::kill(0, Utc.with_ymd_and_hms(2023, 12, 11 + day, hour, 30, 0).unwrap(), killer, victim);
+    }
+
+    #[test]
+    fn buckets_by_weekday_and_hour() {
+        let data = [
+            kill("Alpha", "Bravo", 0, 0),
+            kill("Alpha", "Bravo", 3, 20),
+            kill("Charlie", "ALPHA", 3, 20),
+            kill("Charlie", "Bravo", 6, 23),
+        ];
+        let heatmap = ActivityHeatmap::from_kills(&data, None);
+        assert_eq!(heatmap.total(), 4);
+        assert_eq!(heatmap.counts[0][0], 1);
+        assert_eq!(heatmap.counts[3][20], 2);
+        assert_eq!(heatmap.counts[6][23], 1);
+        assert_eq!(heatmap.peak(), Some(("Thu", 20, 2)));
+
+        let heatmap = ActivityHeatmap::from_kills(&data, Some(&CmdrName::new("alpha")));
+        assert_eq!(heatmap.total(), 3);
+        assert_eq!(heatmap.counts[6][23], 0);
+    }
+
+    #[test]
+    fn text_grid_is_shaded_relative_to_the_peak() {
+        let data = [
+            kill("Alpha", "Bravo", 0, 0),
+            kill("Alpha", "Bravo", 0, 1),
+            kill("Alpha", "Bravo", 0, 1),
+            kill("Alpha", "Bravo", 0, 1),
+            kill("Alpha", "Bravo", 0, 1),
+        ];
+        let grid = ActivityHeatmap::from_kills(&data, None).to_text_grid();
+        let lines: Vec<&str> = grid.lines().collect();
+        assert_eq!(lines.len(), 8);
+        assert!(lines[1].starts_with("Mon ░█·"));
+        assert_eq!(lines[1].chars().count(), 4 + 24);
+        assert_eq!(lines[7], format!("Sun {}", "·".repeat(24)));
+    }
+
+    #[test]
+    fn no_activity() {
+        let heatmap = ActivityHeatmap::from_kills(&[], None);
+        assert_eq!(heatmap.peak(), None);
+        assert!(heatmap.to_text_grid().lines().skip(1).all(|x| x.ends_with(&"·".repeat(24))));
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{data_server::models::HistoryEntryJson, util::{cmdr_name::CmdrName, elite::describe_pilot}};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryEventType {
+    Died,
+    Killed
+}
+
+/// A single engagement from the point of view of one CMDR.
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub event_type: HistoryEventType,
+    pub other_cmdr: String,
+    pub timestamp: String,
+    pub location: Option<String>,
+    /// Journal symbols of the ship and rank of the CMDR the history belongs to
+    pub own_ship: String,
+    pub own_rank: String,
+    /// Journal symbols of the ship and rank of the other CMDR
+    pub other_ship: String,
+    pub other_rank: String
+}
+
+impl HistoryEntry {
+    /// `None` if `self_cmdr` is neither the killer nor the victim.
+    pub fn from_json(data: &HistoryEntryJson, self_cmdr: &CmdrName) -> Option<Self> {
+        let event_type: HistoryEventType;
+        if self_cmdr.matches(&data.killer_name) {
+            event_type = HistoryEventType::Killed;
+        } else if self_cmdr.matches(&data.victim_name) {
+            event_type = HistoryEventType::Died;
+        } else {
+            return None;
+        }
+        let other_cmdr = match event_type {
+            HistoryEventType::Killed => CmdrName::new(&data.victim_name).to_string(),
+            HistoryEventType::Died => CmdrName::new(&data.killer_name).to_string()
+        };
+        let killer = (data.killer_ship.clone(), data.killer_rank.clone());
+        let victim = (data.victim_ship.clone(), data.victim_rank.clone());
+        let ((own_ship, own_rank), (other_ship, other_rank)) = match event_type {
+            HistoryEventType::Killed => (killer, victim),
+            HistoryEventType::Died => (victim, killer)
+        };
+        let location: Option<String>;
+        match data.location.clone() {
+            None => location = None,
+            Some(val) => {
+                if val.to_lowercase().contains("unknown") {
+                    location = None;
+                }
+                else {
+                    location = Some(val);
+                }
+            }
+        }
+        return Some(HistoryEntry { event_type, other_cmdr, timestamp: data.timestamp.clone(), location, own_ship, own_rank, other_ship, other_rank });
+    }
+
+    /// Ship and rank of the CMDR the history belongs to, see [describe_pilot].
+    pub fn own_pilot(&self) -> String {
+        return describe_pilot(&self.own_ship, &self.own_rank);
+    }
+
+    /// Ship and rank of the other CMDR, see [describe_pilot].
+    pub fn other_pilot(&self) -> String {
+        return describe_pilot(&self.other_ship, &self.other_rank);
+    }
+}
+
+/// All entries of `history` that involve `cmdr`, from their point of view.
+pub fn classify_history(history: &[HistoryEntryJson], cmdr: &CmdrName) -> Vec<HistoryEntry> {
+    return history.iter().filter_map(|x| HistoryEntry::from_json(x, cmdr)).collect();
+}
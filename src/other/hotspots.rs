@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+use crate::{data_server::models::RangeResponseEntry, util::cmdr_name::{CmdrName, SpellingCounter}};
+
+/// Kills logged in one system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemTally {
+    pub system: String,
+    pub kills: u32,
+    /// The CMDR with the most kills in the system, and how many
+    pub top_killer: Option<(String, u32)>
+}
+
+#[derive(Default)]
+struct SystemCounter {
+    kills: u32,
+    spellings: HashMap<String, u32>,
+    killers: HashMap<CmdrName, (u32, SpellingCounter)>
+}
+
+/// Kills per system, most kills first. Ties are ordered by name. Kills without a known location are left out.
+/// System names are compared case-insensitively.
+pub fn get_hotspots(data: &[RangeResponseEntry]) -> Vec<SystemTally> {
+    let mut systems: HashMap<String, SystemCounter> = HashMap::new();
+
+    for kill in data {
+        let Some(location) = &kill.location else {
+            continue;
+        };
+        let counter = systems.entry(location.to_lowercase()).or_default();
+        counter.kills += 1;
+        *counter.spellings.entry(location.clone()).or_insert(0) += 1;
+
+        let killer = CmdrName::new(&kill.killer_name);
+        let (kills, spellings) = counter.killers.entry(killer.clone()).or_default();
+        *kills += 1;
+        spellings.add(&killer);
+    }
+
+    let mut result: Vec<SystemTally> = systems.into_values().map(|counter| {
+        let system = counter.spellings.iter()
+            .max_by(|(a_name, a_count), (b_name, b_count)| a_count.cmp(b_count).then_with(|| b_name.cmp(a_name)))
+            .map(|(name, _)| name.to_owned())
+            .unwrap_or_default();
+        let top_killer = counter.killers.iter()
+            .max_by(|(a_name, (a_kills, _)), (b_name, (b_kills, _))| a_kills.cmp(b_kills).then_with(|| b_name.key().cmp(a_name.key())))
+            .map(|(name, (kills, spellings))| (spellings.most_common().unwrap_or_else(|| name.to_string()), *kills));
+        SystemTally { system, kills: counter.kills, top_killer }
+    }).collect();
+
+    result.sort_by(|a, b| b.kills.cmp(&a.kills).then_with(|| a.system.to_lowercase().cmp(&b.system.to_lowercase())));
+    return result;
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::{data_server::models::RangeResponseEntry, test_support};
+
+    use super::{get_hotspots, SystemTally};
+
+    fn kill(id: u64, killer: &str, location: Option<&str>) -> RangeResponseEntry {
+        let timestamp = Utc.with_ymd_and_hms(2023, 12, 14, 12, 0, 0).unwrap();
+        return RangeResponseEntry { location: location.map(|x| x.to_string()), ..test_support::kill(id, timestamp, killer, "Victim") };
+    }
+
+    #[test]
+    fn systems_with_most_kills_first() {
+        let data = [
+            kill(1, "Alpha", Some("Deciat")),
+            kill(2, "Bravo", Some("Shinrarta Dezhra")),
+            kill(3, "alpha", Some("DECIAT")),
+            kill(4, "Bravo", Some("Deciat")),
+            kill(5, "Alpha", None),
+            kill(6, "Charlie", Some("Sol")),
+        ];
+        assert_eq!(get_hotspots(&data), vec![
+            SystemTally { system: "Deciat".to_string(), kills: 3, top_killer: Some(("Alpha".to_string(), 2)) },
+            SystemTally { system: "Shinrarta Dezhra".to_string(), kills: 1, top_killer: Some(("Bravo".to_string(), 1)) },
+            SystemTally { system: "Sol".to_string(), kills: 1, top_killer: Some(("Charlie".to_string(), 1)) },
+        ]);
+    }
+
+    #[test]
+    fn top_killer_ties_go_to_name() {
+        let data = [
+            kill(1, "Bravo", Some("Sol")),
+            kill(2, "Alpha", Some("Sol")),
+        ];
+        assert_eq!(get_hotspots(&data)[0].top_killer, Some(("Alpha".to_string(), 1)));
+    }
+}
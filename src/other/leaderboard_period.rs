@@ -0,0 +1,164 @@
+use std::ops::Add;
+
+use chrono::{DateTime, Utc, Datelike, Days, Months, NaiveDate, NaiveDateTime, TimeZone};
+
+use super::leaderboard_window::{LeaderboardWindow, WindowAnchor};
+
+/// First day of the Elite Dangerous release. Nothing can have been logged before.
+const ALL_TIME_START: (i32, u32, u32) = (2014, 12, 16);
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum LeaderboardPeriod {
+    #[name = "day"]
+    Day,
+    #[name = "week"]
+    Week,
+    #[name = "month"]
+    Month,
+    #[name = "season"]
+    Season,
+    #[name = "recorded"]
+    Recorded,
+    #[name = "custom"]
+    Custom
+}
+
+impl LeaderboardPeriod {
+
+    /// The `(start, end)` range of the period that contains `now`. `None` for [LeaderboardPeriod::Custom], its range is given by the user.
+    /// - Day: the current UTC day
+    /// - Week: the current leaderboard week, see [LeaderboardWindow]
+    /// - Month: the current calendar month
+    /// - Season: the current calendar quarter (Jan-Mar, Apr-Jun, Jul-Sep, Oct-Dec)
+    /// - Recorded: everything the Kill Cache holds up to `now`, starting at `covered_from`. Without it, since the release of the game.
+    pub fn time_range(&self, now: DateTime<Utc>, covered_from: Option<DateTime<Utc>>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let today = now.date_naive();
+        return Some(match self {
+            LeaderboardPeriod::Day => (start_of_day(today), start_of_day(today.add(Days::new(1)))),
+            LeaderboardPeriod::Week => LeaderboardWindow::containing(now, WindowAnchor::from_environment()).as_tuple(),
+            LeaderboardPeriod::Month => {
+                let first_of_month = today.with_day(1).unwrap();
+                (start_of_day(first_of_month), start_of_day(first_of_month.add(Months::new(1))))
+            },
+            LeaderboardPeriod::Season => {
+                let first_month_of_season = (today.month0() / 3) * 3 + 1;
+                let first_of_season = NaiveDate::from_ymd_opt(today.year(), first_month_of_season, 1).unwrap();
+                (start_of_day(first_of_season), start_of_day(first_of_season.add(Months::new(3))))
+            },
+            LeaderboardPeriod::Recorded => (covered_from.unwrap_or_else(all_time_start), now),
+            LeaderboardPeriod::Custom => return None
+        });
+    }
+
+    /// How the period reads in a sentence. `start` is the start of its range, the recorded period names it.
+    pub fn display_name(&self, start: DateTime<Utc>) -> String {
+        return match self {
+            LeaderboardPeriod::Day => "today".to_string(),
+            LeaderboardPeriod::Week => "this leaderboard week".to_string(),
+            LeaderboardPeriod::Month => "this month".to_string(),
+            LeaderboardPeriod::Season => "this season".to_string(),
+            LeaderboardPeriod::Recorded => format!("the time since {}", start.format("%Y-%m-%d")),
+            LeaderboardPeriod::Custom => "the chosen period".to_string()
+        };
+    }
+}
+
+/// Parses a user supplied range bound. Accepts `YYYY-MM-DD`, optionally followed by a UTC time as `HH:MM` or `HH:MM:SS`.
+/// A lone date means the start of that day for the lower bound, and the end of that day for the upper bound.
+pub fn parse_range_bound(input: &str, is_upper_bound: bool) -> Result<DateTime<Utc>, String> {
+    let input = input.trim();
+
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"] {
+        if let Ok(date_time) = NaiveDateTime::parse_from_str(input, format) {
+            return Ok(Utc.from_utc_datetime(&date_time));
+        }
+    }
+
+    return match NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        Ok(date) => match is_upper_bound {
+            false => Ok(start_of_day(date)),
+            true => Ok(start_of_day(date.add(Days::new(1))))
+        },
+        Err(_) => Err(format!("`{}` is not a valid date. Use YYYY-MM-DD, optionally followed by a time as HH:MM (UTC).", input))
+    };
+}
+
+/// Checks that a user supplied range makes sense before anything is fetched for it.
+pub fn validate_custom_range(start: DateTime<Utc>, end: DateTime<Utc>, now: DateTime<Utc>) -> Result<(), String> {
+    if start >= end {
+        return Err("The start of the range must be before its end.".to_string());
+    }
+    if start > now {
+        return Err("The range starts in the future. No kills there… *yet.*".to_string());
+    }
+    if end <= all_time_start() {
+        return Err("The range ends before Elite Dangerous was released.".to_string());
+    }
+    return Ok(());
+}
+
+fn start_of_day(date: NaiveDate) -> DateTime<Utc> {
+    return Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap());
+}
+
+fn all_time_start() -> DateTime<Utc> {
+    let (year, month, day) = ALL_TIME_START;
+    return start_of_day(NaiveDate::from_ymd_opt(year, month, day).unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::{parse_range_bound, validate_custom_range, LeaderboardPeriod};
+
+    #[test]
+    fn fixed_periods_contain_now() {
+        let now = Utc.with_ymd_and_hms(2023, 11, 14, 12, 30, 0).unwrap();
+        let at = |month: u32, day: u32| Utc.with_ymd_and_hms(2023, month, day, 0, 0, 0).unwrap();
+        assert_eq!(LeaderboardPeriod::Day.time_range(now, None), Some((at(11, 14), at(11, 15))));
+        assert_eq!(LeaderboardPeriod::Month.time_range(now, None), Some((at(11, 1), at(12, 1))));
+        assert_eq!(LeaderboardPeriod::Season.time_range(now, None), Some((at(10, 1), Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap())));
+        assert_eq!(LeaderboardPeriod::Custom.time_range(now, None), None);
+    }
+
+    #[test]
+    fn recorded_starts_where_the_cache_does() {
+        let now = Utc.with_ymd_and_hms(2023, 11, 14, 12, 30, 0).unwrap();
+        let covered_from = Utc.with_ymd_and_hms(2023, 10, 15, 12, 30, 0).unwrap();
+        assert_eq!(LeaderboardPeriod::Recorded.time_range(now, Some(covered_from)), Some((covered_from, now)));
+        assert_eq!(LeaderboardPeriod::Recorded.time_range(now, None), Some((Utc.with_ymd_and_hms(2014, 12, 16, 0, 0, 0).unwrap(), now)));
+        assert_eq!(LeaderboardPeriod::Recorded.display_name(covered_from), "the time since 2023-10-15");
+    }
+
+    #[test]
+    fn parses_dates_and_times() {
+        let at = |day: u32, hour: u32, minute: u32| Utc.with_ymd_and_hms(2023, 12, day, hour, minute, 0).unwrap();
+        assert_eq!(parse_range_bound("2023-12-14", false), Ok(at(14, 0, 0)));
+        // A lone upper date includes the whole day
+        assert_eq!(parse_range_bound("2023-12-14", true), Ok(at(15, 0, 0)));
+        assert_eq!(parse_range_bound(" 2023-12-14 08:30 ", true), Ok(at(14, 8, 30)));
+        assert_eq!(parse_range_bound("2023-12-14T08:30:00", false), Ok(at(14, 8, 30)));
+    }
+
+    #[test]
+    fn rejects_malformed_dates() {
+        for input in ["", "yesterday", "14.12.2023", "2023-13-01", "2023-02-30", "2023-12-14 25:00"] {
+            assert!(parse_range_bound(input, false).is_err(), "{} was accepted", input);
+        }
+    }
+
+    #[test]
+    fn validates_ranges() {
+        let now = Utc.with_ymd_and_hms(2023, 12, 14, 12, 0, 0).unwrap();
+        let at = |day: u32| Utc.with_ymd_and_hms(2023, 12, day, 0, 0, 0).unwrap();
+        assert_eq!(validate_custom_range(at(1), at(14), now), Ok(()));
+        // Ending in the future is fine, the range is simply not over yet
+        assert_eq!(validate_custom_range(at(1), at(20), now), Ok(()));
+        assert!(validate_custom_range(at(14), at(1), now).is_err());
+        assert!(validate_custom_range(at(1), at(1), now).is_err());
+        assert!(validate_custom_range(at(15), at(20), now).is_err());
+        let before_release = Utc.with_ymd_and_hms(2014, 1, 1, 0, 0, 0).unwrap();
+        assert!(validate_custom_range(before_release, before_release + chrono::Duration::days(1), now).is_err());
+    }
+}
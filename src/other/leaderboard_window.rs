@@ -0,0 +1,201 @@
+use std::{fmt::Display, ops::{Add, Sub}};
+
+use chrono::{DateTime, Datelike, Days, NaiveDate, TimeZone, Utc, Weekday};
+
+use crate::data::Environment;
+
+/// Weekday and full hour (UTC) a leaderboard week starts and ends on. Defaults to Thursday 08:00 UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowAnchor {
+    pub weekday: Weekday,
+    pub hour: u32
+}
+
+impl WindowAnchor {
+
+    /// Panics if `hour` is not a valid hour of the day. The Environment is validated on startup, so this only catches programming errors.
+    pub fn new(weekday: Weekday, hour: u32) -> Self {
+        assert!(hour < 24, "Anchor hour must be between 0 and 23, got {}", hour);
+        return WindowAnchor { weekday, hour };
+    }
+
+    pub fn from_environment() -> Self {
+        return WindowAnchor::new(Environment::leaderboard_anchor_weekday(), Environment::leaderboard_anchor_hour());
+    }
+
+    fn at_date(&self, date: NaiveDate) -> DateTime<Utc> {
+        return Utc.from_utc_datetime(&date.and_hms_opt(self.hour, 0, 0).unwrap());
+    }
+}
+
+impl Display for WindowAnchor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {:02}:00 UTC", self.weekday, self.hour)
+    }
+}
+
+/// A single leaderboard week. `start` is inclusive, `end` is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LeaderboardWindow {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>
+}
+
+impl LeaderboardWindow {
+
+    /// The window `instant` falls into. An instant exactly on the anchor belongs to the window that starts there.
+    pub fn containing(instant: DateTime<Utc>, anchor: WindowAnchor) -> Self {
+        let days_since_anchor_weekday = (7 + instant.weekday().num_days_from_monday() - anchor.weekday.num_days_from_monday()) % 7;
+        let mut start = anchor.at_date(instant.date_naive().sub(Days::new(u64::from(days_since_anchor_weekday))));
+        if start > instant {
+            // Same weekday as the anchor, but before the anchor hour
+            start = start.sub(Days::new(7));
+        }
+        return LeaderboardWindow::starting_at(start);
+    }
+
+    /// The window that is running right now.
+    pub fn current() -> Self {
+        return LeaderboardWindow::containing(Utc::now(), WindowAnchor::from_environment());
+    }
+
+    /// The most recent window that has already ended at the anchor hour of `date`.
+    /// For a date on the anchor weekday this is the window ending on that very day.
+    pub fn ended_by(date: NaiveDate, anchor: WindowAnchor) -> Self {
+        return LeaderboardWindow::containing(anchor.at_date(date), anchor).previous();
+    }
+
+    pub fn previous(&self) -> Self {
+        return LeaderboardWindow::starting_at(self.start.sub(Days::new(7)));
+    }
+
+    pub fn as_tuple(&self) -> (DateTime<Utc>, DateTime<Utc>) {
+        return (self.start, self.end);
+    }
+
+    fn starting_at(start: DateTime<Utc>) -> Self {
+        return LeaderboardWindow { start, end: start.add(Days::new(7)) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveDate, TimeZone, Utc, Weekday, DateTime};
+
+    use super::{LeaderboardWindow, WindowAnchor};
+
+    fn thursday_0800() -> WindowAnchor {
+        return WindowAnchor::new(Weekday::Thu, 8);
+    }
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32, second: u32) -> DateTime<Utc> {
+        return Utc.with_ymd_and_hms(year, month, day, hour, minute, second).unwrap();
+    }
+
+    /// 2023-12-14 is a Thursday.
+    fn window_starting(day: u32) -> LeaderboardWindow {
+        let start = utc(2023, 12, day, 8, 0, 0);
+        return LeaderboardWindow { start, end: start + Duration::days(7) };
+    }
+
+    #[test]
+    fn every_weekday_before_and_after_the_anchor_hour() {
+        // (day in December 2023, weekday, window start day before 08:00, window start day from 08:00 on)
+        let cases = [
+            (11, Weekday::Mon, 7, 7),
+            (12, Weekday::Tue, 7, 7),
+            (13, Weekday::Wed, 7, 7),
+            (14, Weekday::Thu, 7, 14),
+            (15, Weekday::Fri, 14, 14),
+            (16, Weekday::Sat, 14, 14),
+            (17, Weekday::Sun, 14, 14),
+        ];
+        for (day, weekday, start_before, start_after) in cases {
+            let before = utc(2023, 12, day, 7, 59, 59);
+            let after = utc(2023, 12, day, 8, 0, 0);
+            assert_eq!(chrono::Datelike::weekday(&before), weekday);
+            assert_eq!(LeaderboardWindow::containing(before, thursday_0800()), window_starting(start_before), "{} 07:59:59", weekday);
+            assert_eq!(LeaderboardWindow::containing(after, thursday_0800()), window_starting(start_after), "{} 08:00:00", weekday);
+        }
+    }
+
+    #[test]
+    fn every_hour_of_a_week_is_inside_its_window() {
+        let first = utc(2023, 12, 11, 0, 0, 0);
+        for hour in 0..(24 * 14) {
+            let instant = first + Duration::hours(hour) + Duration::minutes(30);
+            let window = LeaderboardWindow::containing(instant, thursday_0800());
+            assert!(window.start <= instant && instant < window.end, "{} not in {:?}", instant, window);
+            assert_eq!(window.end - window.start, Duration::days(7));
+            assert_eq!(chrono::Datelike::weekday(&window.start), Weekday::Thu);
+            assert_eq!(chrono::Timelike::hour(&window.start), 8);
+        }
+    }
+
+    #[test]
+    fn the_anchor_instant_starts_a_new_window() {
+        let anchor_instant = utc(2023, 12, 14, 8, 0, 0);
+        let window = LeaderboardWindow::containing(anchor_instant, thursday_0800());
+        assert_eq!(window.start, anchor_instant);
+        assert_eq!(LeaderboardWindow::containing(anchor_instant - Duration::nanoseconds(1), thursday_0800()).end, anchor_instant);
+    }
+
+    #[test]
+    fn previous_is_adjacent() {
+        let window = window_starting(14);
+        assert_eq!(window.previous(), window_starting(7));
+        assert_eq!(window.previous().end, window.start);
+        assert_eq!(window_starting(21).previous(), window);
+    }
+
+    #[test]
+    fn ended_by_every_weekday() {
+        // (day in December 2023, start day of the window that ended most recently at 08:00 that day)
+        let cases = [(11, 30), (12, 30), (13, 30), (14, 7), (15, 7), (16, 7), (17, 7)];
+        for (day, start_day) in cases {
+            let date = NaiveDate::from_ymd_opt(2023, 12, day).unwrap();
+            let expected = match start_day {
+                30 => {
+                    let start = utc(2023, 11, 30, 8, 0, 0);
+                    LeaderboardWindow { start, end: start + Duration::days(7) }
+                },
+                e => window_starting(e)
+            };
+            assert_eq!(LeaderboardWindow::ended_by(date, thursday_0800()), expected, "{}", date);
+        }
+    }
+
+    #[test]
+    fn ended_by_anchor_day_is_the_window_the_scheduled_summary_reports() {
+        // The job fires on the anchor, and reports the window that just ended
+        let fired_at = utc(2023, 12, 14, 8, 0, 0);
+        let reported = LeaderboardWindow::containing(fired_at, thursday_0800()).previous();
+        assert_eq!(reported, LeaderboardWindow::ended_by(fired_at.date_naive(), thursday_0800()));
+        assert_eq!(reported.end, fired_at);
+    }
+
+    #[test]
+    fn custom_anchor() {
+        let anchor = WindowAnchor::new(Weekday::Sun, 23);
+        assert_eq!(LeaderboardWindow::containing(utc(2023, 12, 17, 22, 59, 59), anchor).start, utc(2023, 12, 10, 23, 0, 0));
+        assert_eq!(LeaderboardWindow::containing(utc(2023, 12, 17, 23, 0, 0), anchor).start, utc(2023, 12, 17, 23, 0, 0));
+        assert_eq!(LeaderboardWindow::containing(utc(2023, 12, 18, 0, 0, 0), anchor).start, utc(2023, 12, 17, 23, 0, 0));
+
+        let midnight_monday = WindowAnchor::new(Weekday::Mon, 0);
+        assert_eq!(LeaderboardWindow::containing(utc(2023, 12, 11, 0, 0, 0), midnight_monday).start, utc(2023, 12, 11, 0, 0, 0));
+        assert_eq!(LeaderboardWindow::containing(utc(2023, 12, 10, 23, 59, 59), midnight_monday).start, utc(2023, 12, 4, 0, 0, 0));
+    }
+
+    #[test]
+    fn across_year_boundary() {
+        let window = LeaderboardWindow::containing(utc(2024, 1, 2, 12, 0, 0), thursday_0800());
+        assert_eq!(window.start, utc(2023, 12, 28, 8, 0, 0));
+        assert_eq!(window.end, utc(2024, 1, 4, 8, 0, 0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_anchor_hour() {
+        WindowAnchor::new(Weekday::Thu, 24);
+    }
+}
@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use poise::serenity_prelude::{Http, ChannelId, Color, CreateEmbed};
+
+use crate::{data::Environment, data_server::models::RangeResponseEntry, kill_cache::{KillCache, KillCacheError}, util::{cmdr_name::CmdrName, elite::ship_name}};
+
+/// Both the background sync and the webhook publish kills. Only one of them may do so at a time, otherwise kills get posted twice.
+static PUBLISH_LOCK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+/// Discord allows up to 10 Embeds per Message.
+const KILLS_PER_MESSAGE: usize = 10;
+/// Pause between two Messages, keeps us well below Discord's rate limit of 5 Messages per 5 seconds and channel.
+const PAUSE_BETWEEN_MESSAGES: Duration = Duration::from_secs(2);
+
+/// Posts every kill queued by the sync or the webhook to the live feed channel, see [KillCache::take_live_feed_kills].
+/// Without a live feed channel the queue is only emptied.
+///
+/// Kills are taken off the queue before posting, a failing post should not result in the same kills being posted over and over.
+/// If more than the configured maximum arrives at once, only the oldest ones are posted, followed by a note how many were skipped.
+pub async fn publish_new_kills(cache: &KillCache) -> Result<(), KillCacheError> {
+    let _guard = PUBLISH_LOCK.lock().await;
+    let new_kills = cache.take_live_feed_kills()?;
+
+    let channel_id = match Environment::live_feed_channel_id() {
+        None => return Ok(()),
+        Some(e) => ChannelId(e)
+    };
+    if new_kills.is_empty() {
+        return Ok(());
+    }
+
+    let max_kills = Environment::live_feed_max_kills_per_batch();
+    let skipped = new_kills.len().saturating_sub(max_kills);
+    let client = Http::new(&Environment::discord_token());
+
+    for (i, chunk) in new_kills.iter().take(max_kills).collect::<Vec<_>>().chunks(KILLS_PER_MESSAGE).enumerate() {
+        if i > 0 {
+            tokio::time::sleep(PAUSE_BETWEEN_MESSAGES).await;
+        }
+        let message_sent_result = channel_id.send_message(&client, |m| {
+            for kill in chunk {
+                m.add_embed(|embed| build_kill_embed(kill, embed));
+            }
+            m
+        }).await;
+        if let Err(err) = message_sent_result {
+            println!("Failed to send live feed message: {}", err);
+        }
+    }
+
+    if skipped > 0 {
+        tokio::time::sleep(PAUSE_BETWEEN_MESSAGES).await;
+        let message_sent_result = channel_id.send_message(&client, |m| m.content(format!("…and {} more kills. Check `/pvpweekly` for the full picture.", skipped))).await;
+        if let Err(err) = message_sent_result {
+            println!("Failed to send live feed message: {}", err);
+        }
+    }
+
+    return Ok(());
+}
+
+/// " in a Mamba", if the ship is known.
+fn flying(ship: &Option<String>) -> String {
+    return match ship.as_deref().and_then(ship_name) {
+        Some(ship) => format!(" in a {}", ship),
+        None => "".to_string()
+    };
+}
+
+fn build_kill_embed<'a>(kill: &RangeResponseEntry, embed: &'a mut CreateEmbed) -> &'a mut CreateEmbed {
+    embed.color(Color::DARK_RED)
+        .description(format!(
+            ":dagger: **{}**{} killed **{}**{}",
+            CmdrName::new(&kill.killer_name), flying(&kill.killer_ship),
+            CmdrName::new(&kill.victim_name), flying(&kill.victim_ship)
+        ))
+        .timestamp(kill.timestamp);
+    if let Some(location) = &kill.location {
+        embed.footer(|f| f.text(location));
+    }
+    return embed;
+}
@@ -1,2 +1,13 @@
 pub mod weekly_summary;
-pub mod weekly_summary_event;
\ No newline at end of file
+pub mod weekly_summary_event;
+pub mod live_feed;
+pub mod leaderboard_period;
+pub mod leaderboard_window;
+pub mod history;
+pub mod ship_stats;
+pub mod rivals;
+pub mod versus;
+pub mod hotspots;
+pub mod activity;
+pub mod trends;
+pub mod week_comparison;
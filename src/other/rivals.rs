@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use crate::util::cmdr_name::{CmdrName, SpellingCounter};
+
+use super::history::{HistoryEntry, HistoryEventType};
+
+/// The head-to-head record of a CMDR against one other CMDR.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rival {
+    pub cmdr: String,
+    /// How often the CMDR killed the rival
+    pub kills: u32,
+    /// How often the rival killed the CMDR
+    pub deaths: u32
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rivals {
+    /// The CMDR that killed them most
+    pub nemesis: Option<Rival>,
+    /// The CMDR they killed most
+    pub favourite_target: Option<Rival>
+}
+
+/// Head-to-head records against every CMDR in `history`, keyed by the normalized name.
+pub fn get_head_to_head(history: &[HistoryEntry]) -> Vec<Rival> {
+    let mut records: HashMap<CmdrName, (Rival, SpellingCounter)> = HashMap::new();
+
+    for entry in history {
+        let other = CmdrName::new(&entry.other_cmdr);
+        let (record, spellings) = records.entry(other.clone())
+            .or_insert_with(|| (Rival { cmdr: other.display().to_string(), kills: 0, deaths: 0 }, SpellingCounter::default()));
+        spellings.add(&other);
+        match entry.event_type {
+            HistoryEventType::Killed => record.kills += 1,
+            HistoryEventType::Died => record.deaths += 1
+        }
+    }
+
+    return records.into_values().map(|(mut record, spellings)| {
+        if let Some(spelling) = spellings.most_common() {
+            record.cmdr = spelling;
+        }
+        record
+    }).collect();
+}
+
+/// Finds nemesis and favourite target. Ties go to the CMDR with more engagements overall, then by name.
+pub fn get_rivals(history: &[HistoryEntry]) -> Rivals {
+    let records = get_head_to_head(history);
+
+    let pick = |count: fn(&Rival) -> u32| {
+        return records.iter()
+            .filter(|x| count(x) > 0)
+            .max_by(|a, b| count(a).cmp(&count(b))
+                .then_with(|| (a.kills + a.deaths).cmp(&(b.kills + b.deaths)))
+                .then_with(|| CmdrName::new(&b.cmdr).key().cmp(CmdrName::new(&a.cmdr).key())))
+            .cloned();
+    };
+
+    return Rivals {
+        nemesis: pick(|x| x.deaths),
+        favourite_target: pick(|x| x.kills)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{other::history::{HistoryEntry, HistoryEventType}, test_support::history_entry};
+
+    use super::{get_rivals, Rival, Rivals};
+
+    fn entry(event_type: HistoryEventType, other_cmdr: &str) -> HistoryEntry {
+        return history_entry(event_type, other_cmdr, "2023-12-14 12:00:00");
+    }
+
+    fn rival(cmdr: &str, kills: u32, deaths: u32) -> Option<Rival> {
+        return Some(Rival { cmdr: cmdr.to_string(), kills, deaths });
+    }
+
+    #[test]
+    fn nemesis_and_favourite_target() {
+        use HistoryEventType::{Died, Killed};
+        let history = [
+            entry(Died, "Nemesis"),
+            entry(Died, "NEMESIS"),
+            entry(Died, "Nemesis"),
+            entry(Killed, "Nemesis"),
+            entry(Killed, "Victim"),
+            entry(Killed, "Victim"),
+            entry(Died, "Other"),
+        ];
+        assert_eq!(get_rivals(&history), Rivals {
+            nemesis: rival("Nemesis", 1, 3),
+            favourite_target: rival("Victim", 2, 0)
+        });
+    }
+
+    #[test]
+    fn ties_go_to_more_engagements_then_name() {
+        use HistoryEventType::{Died, Killed};
+        let history = [
+            entry(Killed, "Bravo"),
+            entry(Killed, "Alpha"),
+            entry(Killed, "Charlie"),
+            entry(Died, "Charlie"),
+        ];
+        let rivals = get_rivals(&history);
+        assert_eq!(rivals.favourite_target, rival("Charlie", 1, 1));
+        assert_eq!(rivals.nemesis, rival("Charlie", 1, 1));
+
+        let rivals = get_rivals(&history[..2]);
+        assert_eq!(rivals.favourite_target, rival("Alpha", 1, 0));
+        assert_eq!(rivals.nemesis, None);
+    }
+}
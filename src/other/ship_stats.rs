@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use crate::util::elite::ship_name;
+
+use super::history::{HistoryEntry, HistoryEventType};
+
+/// Kills and deaths of a CMDR while flying one hull. `ship` is the display name, `None` for engagements where the ship is unknown.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShipTally {
+    pub ship: Option<String>,
+    pub kills: u32,
+    pub deaths: u32
+}
+
+impl ShipTally {
+    pub fn engagements(&self) -> u32 {
+        return self.kills + self.deaths;
+    }
+
+    /// Without any deaths, the K/D is the amount of kills
+    pub fn kill_death_ratio(&self) -> f64 {
+        return f64::from(self.kills) / f64::from(self.deaths.max(1));
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShipStats {
+    /// Every hull the CMDR flew, most engagements first
+    pub ships: Vec<ShipTally>,
+    /// The enemy hull the CMDR killed most, and how often
+    pub most_killed_enemy_ship: Option<(String, u32)>,
+    /// The hull with the best K/D among those with at least the minimum engagements
+    pub best_kill_death_ship: Option<ShipTally>
+}
+
+/// Aggregates a CMDR's history by the ship they flew.
+/// A hull needs at least `min_engagements` kills and deaths combined to be considered for the best K/D, otherwise a single lucky kill wins.
+pub fn get_ship_stats(history: &[HistoryEntry], min_engagements: u32) -> ShipStats {
+    let mut ships: HashMap<Option<String>, ShipTally> = HashMap::new();
+    let mut enemy_ships: HashMap<String, u32> = HashMap::new();
+
+    for entry in history {
+        let own_ship = ship_name(&entry.own_ship);
+        let tally = ships.entry(own_ship.clone()).or_insert_with(|| ShipTally { ship: own_ship, kills: 0, deaths: 0 });
+        match entry.event_type {
+            HistoryEventType::Killed => {
+                tally.kills += 1;
+                if let Some(enemy_ship) = ship_name(&entry.other_ship) {
+                    *enemy_ships.entry(enemy_ship).or_insert(0) += 1;
+                }
+            },
+            HistoryEventType::Died => tally.deaths += 1
+        }
+    }
+
+    let mut ships: Vec<ShipTally> = ships.into_values().collect();
+    // Unknown ships last, ties by name so the order is stable
+    ships.sort_by(|a, b| b.engagements().cmp(&a.engagements())
+        .then_with(|| a.ship.is_none().cmp(&b.ship.is_none()))
+        .then_with(|| a.ship.cmp(&b.ship)));
+
+    let most_killed_enemy_ship = enemy_ships.into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)));
+
+    let best_kill_death_ship = ships.iter()
+        .filter(|x| x.ship.is_some() && x.engagements() >= min_engagements)
+        .max_by(|a, b| a.kill_death_ratio().total_cmp(&b.kill_death_ratio())
+            .then_with(|| a.engagements().cmp(&b.engagements()))
+            .then_with(|| b.ship.cmp(&a.ship)))
+        .cloned();
+
+    return ShipStats { ships, most_killed_enemy_ship, best_kill_death_ship };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{other::history::{HistoryEntry, HistoryEventType}, test_support::history_entry};
+
+    use super::{get_ship_stats, ShipTally};
+
+    fn entry(event_type: HistoryEventType, own_ship: &str, other_ship: &str) -> HistoryEntry {
+        return HistoryEntry { own_ship: own_ship.to_string(), other_ship: other_ship.to_string(), ..history_entry(event_type, "Other", "2023-12-14 12:00:00") };
+    }
+
+    fn tally(ship: Option<&str>, kills: u32, deaths: u32) -> ShipTally {
+        return ShipTally { ship: ship.map(|x| x.to_string()), kills, deaths };
+    }
+
+    #[test]
+    fn aggregates_by_hull() {
+        use HistoryEventType::{Died, Killed};
+        let history = [
+            entry(Killed, "ferdelance", "Federation_Corvette"),
+            entry(Killed, "FerDeLance", "federation_corvette"),
+            entry(Died, "ferdelance", "mamba"),
+            entry(Killed, "mamba", "python"),
+            entry(Killed, "mamba", "Unknown"),
+            entry(Killed, "Unknown", "python"),
+            entry(Killed, "mamba", "federation_corvette"),
+        ];
+        let stats = get_ship_stats(&history, 3);
+
+        assert_eq!(stats.ships, vec![
+            tally(Some("Fer-de-Lance"), 2, 1),
+            tally(Some("Mamba"), 3, 0),
+            tally(None, 1, 0),
+        ]);
+        assert_eq!(stats.most_killed_enemy_ship, Some(("Federal Corvette".to_string(), 3)));
+        assert_eq!(stats.best_kill_death_ship, Some(tally(Some("Mamba"), 3, 0)));
+    }
+
+    #[test]
+    fn best_kill_death_needs_enough_engagements() {
+        use HistoryEventType::{Died, Killed};
+        let history = [
+            entry(Killed, "eagle", "python"),
+            entry(Killed, "mamba", "python"),
+            entry(Died, "mamba", "python"),
+        ];
+        let stats = get_ship_stats(&history, 2);
+        assert_eq!(stats.best_kill_death_ship, Some(tally(Some("Mamba"), 1, 1)));
+        assert_eq!(get_ship_stats(&history, 3).best_kill_death_ship, None);
+    }
+
+    #[test]
+    fn empty_history() {
+        let stats = get_ship_stats(&[], 1);
+        assert!(stats.ships.is_empty());
+        assert_eq!(stats.most_killed_enemy_ship, None);
+        assert_eq!(stats.best_kill_death_ship, None);
+    }
+}
@@ -0,0 +1,114 @@
+use chrono::{Datelike, Months, NaiveDate, NaiveDateTime};
+
+use crate::data_server::models::RangeResponseEntry;
+
+use super::{history::{HistoryEntry, HistoryEventType}, leaderboard_window::LeaderboardWindow};
+
+/// `weeks` windows ending with `latest`, oldest first.
+pub fn last_windows(latest: LeaderboardWindow, weeks: usize) -> Vec<LeaderboardWindow> {
+    let mut windows = vec![latest];
+    for _ in 1..weeks {
+        let previous = windows.last().unwrap().previous();
+        windows.push(previous);
+    }
+    windows.reverse();
+    return windows;
+}
+
+/// How many kills of `data` fall into each of `windows`.
+pub fn kills_per_window(data: &[RangeResponseEntry], windows: &[LeaderboardWindow]) -> Vec<u32> {
+    return windows.iter()
+        .map(|window| data.iter().filter(|x| x.timestamp >= window.start && x.timestamp < window.end).count() as u32)
+        .collect();
+}
+
+/// Kills and deaths of a CMDR in one calendar month.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimelineMonth {
+    /// First day of the month
+    pub month: NaiveDate,
+    pub kills: u32,
+    pub deaths: u32
+}
+
+/// Kills and deaths per month from the first to the last month of `history`, including the quiet months in between.
+/// Entries with a timestamp that can not be parsed are left out.
+pub fn monthly_timeline(history: &[HistoryEntry]) -> Vec<TimelineMonth> {
+    let mut months: Vec<(NaiveDate, HistoryEventType)> = history.iter()
+        .filter_map(|entry| {
+            let timestamp = NaiveDateTime::parse_from_str(&entry.timestamp, "%Y-%m-%d %H:%M:%S").ok()?;
+            return Some((timestamp.date().with_day(1)?, entry.event_type));
+        })
+        .collect();
+    months.sort_by_key(|x| x.0);
+
+    let (Some(first), Some(last)) = (months.first().map(|x| x.0), months.last().map(|x| x.0)) else {
+        return vec![];
+    };
+
+    let mut timeline: Vec<TimelineMonth> = vec![];
+    let mut month = first;
+    while month <= last {
+        timeline.push(TimelineMonth { month, kills: 0, deaths: 0 });
+        month = month + Months::new(1);
+    }
+    for (month, event_type) in months {
+        // Both lists are sorted and the timeline has no gaps, so the offset in months is the index
+        let index = ((month.year() - first.year()) * 12 + month.month() as i32 - first.month() as i32) as usize;
+        match event_type {
+            HistoryEventType::Killed => timeline[index].kills += 1,
+            HistoryEventType::Died => timeline[index].deaths += 1
+        }
+    }
+    return timeline;
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    use crate::{data_server::models::RangeResponseEntry, other::{history::{HistoryEntry, HistoryEventType}, leaderboard_window::LeaderboardWindow}, test_support::{self, history_entry}};
+
+    use super::{kills_per_window, last_windows, monthly_timeline, TimelineMonth};
+
+    fn entry(event_type: HistoryEventType, timestamp: &str) -> HistoryEntry {
+        return history_entry(event_type, "Other", timestamp);
+    }
+
+    fn kill(day: u32, hour: u32) -> RangeResponseEntry {
+        return test_support::kill(0, Utc.with_ymd_and_hms(2023, 12, day, hour, 0, 0).unwrap(), "Alpha", "Bravo");
+    }
+
+    #[test]
+    fn counts_kills_per_week() {
+        // 2023-12-14 is a Thursday
+        let start = Utc.with_ymd_and_hms(2023, 12, 14, 8, 0, 0).unwrap();
+        let latest = LeaderboardWindow { start, end: start + chrono::Duration::days(7) };
+        let windows = last_windows(latest, 3);
+        assert_eq!(windows.len(), 3);
+        assert_eq!(windows[0].start, Utc.with_ymd_and_hms(2023, 11, 30, 8, 0, 0).unwrap());
+        assert_eq!(windows[2], latest);
+
+        let data = [kill(1, 12), kill(14, 7), kill(14, 8), kill(20, 12), kill(21, 8)];
+        assert_eq!(kills_per_window(&data, &windows), vec![1, 1, 2]);
+    }
+
+    #[test]
+    fn timeline_fills_quiet_months() {
+        use HistoryEventType::{Died, Killed};
+        let history = [
+            entry(Killed, "2024-01-03 12:00:00"),
+            entry(Died, "2023-11-20 12:00:00"),
+            entry(Killed, "2023-11-01 00:00:00"),
+            entry(Killed, "2024-01-31 23:59:59"),
+            entry(Killed, "not a timestamp"),
+        ];
+        let month = |month: u32, year: i32| NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+        assert_eq!(monthly_timeline(&history), vec![
+            TimelineMonth { month: month(11, 2023), kills: 1, deaths: 1 },
+            TimelineMonth { month: month(12, 2023), kills: 0, deaths: 0 },
+            TimelineMonth { month: month(1, 2024), kills: 2, deaths: 0 },
+        ]);
+        assert_eq!(monthly_timeline(&[]), vec![]);
+    }
+}
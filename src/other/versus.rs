@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use crate::util::cmdr_name::CmdrName;
+
+use super::history::{HistoryEntry, HistoryEventType};
+
+/// One engagement between two CMDRs and the score after it.
+#[derive(Debug, Clone)]
+pub struct VersusEngagement {
+    pub entry: HistoryEntry,
+    /// Kills of the first CMDR against the second, including this engagement
+    pub score_a: u32,
+    /// Kills of the second CMDR against the first, including this engagement
+    pub score_b: u32
+}
+
+#[derive(Debug, Clone)]
+pub struct Versus {
+    /// Oldest first, from the point of view of the first CMDR
+    pub engagements: Vec<VersusEngagement>,
+    /// Systems they fought in with the number of engagements there, most first
+    pub systems: Vec<(String, u32)>
+}
+
+impl Versus {
+
+    pub fn kills_a(&self) -> u32 {
+        return self.engagements.last().map(|x| x.score_a).unwrap_or(0);
+    }
+
+    pub fn kills_b(&self) -> u32 {
+        return self.engagements.last().map(|x| x.score_b).unwrap_or(0);
+    }
+
+    pub fn first_encounter(&self) -> Option<&str> {
+        return self.engagements.first().map(|x| x.entry.timestamp.as_str());
+    }
+
+    pub fn last_encounter(&self) -> Option<&str> {
+        return self.engagements.last().map(|x| x.entry.timestamp.as_str());
+    }
+}
+
+/// Every engagement in `history_a`, the classified history of the first CMDR, against `cmdr_b`.
+pub fn get_versus(history_a: &[HistoryEntry], cmdr_b: &CmdrName) -> Versus {
+    let mut entries: Vec<&HistoryEntry> = history_a.iter().filter(|x| cmdr_b.matches(&x.other_cmdr)).collect();
+    // Timestamps are `YYYY-MM-DD HH:MM:SS`, so they sort chronologically as strings
+    entries.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+
+    let mut engagements: Vec<VersusEngagement> = vec![];
+    let mut system_counts: HashMap<String, u32> = HashMap::new();
+    let (mut score_a, mut score_b) = (0, 0);
+    for entry in entries {
+        match entry.event_type {
+            HistoryEventType::Killed => score_a += 1,
+            HistoryEventType::Died => score_b += 1
+        }
+        if let Some(location) = &entry.location {
+            *system_counts.entry(location.clone()).or_insert(0) += 1;
+        }
+        engagements.push(VersusEngagement { entry: entry.clone(), score_a, score_b });
+    }
+
+    let mut systems: Vec<(String, u32)> = system_counts.into_iter().collect();
+    systems.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    return Versus { engagements, systems };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{other::history::{HistoryEntry, HistoryEventType}, test_support::history_entry, util::cmdr_name::CmdrName};
+
+    use super::get_versus;
+
+    fn entry(event_type: HistoryEventType, other_cmdr: &str, timestamp: &str, location: Option<&str>) -> HistoryEntry {
+        return HistoryEntry { location: location.map(|x| x.to_string()), ..history_entry(event_type, other_cmdr, timestamp) };
+    }
+
+    #[test]
+    fn running_score_is_in_chronological_order() {
+        use HistoryEventType::{Died, Killed};
+        let history = [
+            entry(Killed, "Bravo", "2023-12-14 12:00:00", Some("Shinrarta Dezhra")),
+            entry(Died, "BRAVO", "2023-12-01 08:00:00", Some("Deciat")),
+            entry(Killed, "Charlie", "2023-12-02 08:00:00", Some("Deciat")),
+            entry(Killed, "Bravo", "2023-12-07 20:00:00", Some("Deciat")),
+            entry(Died, "Bravo", "2023-12-20 20:00:00", None),
+        ];
+        let versus = get_versus(&history, &CmdrName::new("bravo"));
+
+        let scores: Vec<(u32, u32)> = versus.engagements.iter().map(|x| (x.score_a, x.score_b)).collect();
+        assert_eq!(scores, vec![(0, 1), (1, 1), (2, 1), (2, 2)]);
+        assert_eq!((versus.kills_a(), versus.kills_b()), (2, 2));
+        assert_eq!(versus.first_encounter(), Some("2023-12-01 08:00:00"));
+        assert_eq!(versus.last_encounter(), Some("2023-12-20 20:00:00"));
+        assert_eq!(versus.systems, vec![("Deciat".to_string(), 2), ("Shinrarta Dezhra".to_string(), 1)]);
+    }
+
+    #[test]
+    fn never_met() {
+        let history = [entry(HistoryEventType::Killed, "Charlie", "2023-12-02 08:00:00", None)];
+        let versus = get_versus(&history, &CmdrName::new("Bravo"));
+        assert!(versus.engagements.is_empty());
+        assert_eq!((versus.kills_a(), versus.kills_b()), (0, 0));
+        assert_eq!(versus.first_encounter(), None);
+    }
+}
@@ -0,0 +1,181 @@
+use crate::util::cmdr_name::CmdrName;
+
+use super::weekly_summary::RankedTally;
+
+/// Climbers and fallers listed in the summary.
+const MAX_MOVES: usize = 3;
+
+/// A CMDR that was ranked the week before, and is on another rank or not ranked at all now.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RankMove {
+    pub cmdr: String,
+    pub previous_rank: u32,
+    /// `None` if they dropped off the leaderboard
+    pub rank: Option<u32>
+}
+
+impl RankMove {
+    /// Positive for climbers, negative for fallers. `None` for CMDRs that dropped off the leaderboard.
+    pub fn change(&self) -> Option<i64> {
+        return self.rank.map(|rank| i64::from(self.previous_rank) - i64::from(rank));
+    }
+}
+
+/// How a leaderboard week compares to the weeks before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeekComparison {
+    pub total_kills: u32,
+    pub previous_total_kills: u32,
+    /// CMDRs in the top of the week that were not in the top the week before, with their rank
+    pub new_in_top: Vec<(String, u32)>,
+    /// Biggest climbs first
+    pub climbers: Vec<RankMove>,
+    /// Biggest falls first, starting with the CMDRs that dropped off the leaderboard
+    pub fallers: Vec<RankMove>,
+    /// CMDRs ranked #1 for at least two weeks in a row up to this week, with the number of weeks
+    pub champion_streaks: Vec<(String, usize)>
+}
+
+impl WeekComparison {
+    pub fn total_kills_change(&self) -> i64 {
+        return i64::from(self.total_kills) - i64::from(self.previous_total_kills);
+    }
+}
+
+fn find<'a>(leaderboard: &'a [RankedTally], cmdr: &CmdrName) -> Option<&'a RankedTally> {
+    return leaderboard.iter().find(|x| cmdr.matches(&x.tally.cmdr));
+}
+
+fn total_kills(leaderboard: &[RankedTally]) -> u32 {
+    return leaderboard.iter().map(|x| x.tally.kills).sum();
+}
+
+/// Compares kill leaderboards of consecutive weeks, newest first. `weeks[0]` is the week that is reported on and needs a week before it.
+/// Only CMDRs in the first `top` ranks of either week count as climbers or fallers, the rest of the board moves around too much.
+/// Streaks only count within `weeks`, so they are at most as long as the weeks given.
+pub fn compare_weeks(weeks: &[Vec<RankedTally>], top: u32) -> Option<WeekComparison> {
+    let (current, previous) = match weeks {
+        [current, previous, ..] => (current, previous),
+        _ => return None
+    };
+
+    let mut new_in_top: Vec<(String, u32)> = vec![];
+    let mut moves: Vec<RankMove> = vec![];
+    for entry in current {
+        let cmdr = CmdrName::new(&entry.tally.cmdr);
+        let previous_rank = find(previous, &cmdr).map(|x| x.rank);
+        if entry.rank <= top && previous_rank.map(|x| x > top).unwrap_or(true) {
+            new_in_top.push((entry.tally.cmdr.clone(), entry.rank));
+        }
+        if let Some(previous_rank) = previous_rank {
+            if previous_rank != entry.rank && (entry.rank <= top || previous_rank <= top) {
+                moves.push(RankMove { cmdr: entry.tally.cmdr.clone(), previous_rank, rank: Some(entry.rank) });
+            }
+        }
+    }
+    for entry in previous.iter().filter(|x| x.rank <= top) {
+        if find(current, &CmdrName::new(&entry.tally.cmdr)).is_none() {
+            moves.push(RankMove { cmdr: entry.tally.cmdr.clone(), previous_rank: entry.rank, rank: None });
+        }
+    }
+
+    let mut climbers: Vec<RankMove> = moves.iter().filter(|x| x.change().map(|x| x > 0).unwrap_or(false)).cloned().collect();
+    climbers.sort_by(|a, b| b.change().cmp(&a.change()).then_with(|| a.rank.cmp(&b.rank)));
+    climbers.truncate(MAX_MOVES);
+    let mut fallers: Vec<RankMove> = moves.into_iter().filter(|x| x.change().map(|x| x < 0).unwrap_or(true)).collect();
+    // `None` sorts first, so the CMDRs that dropped off lead the list
+    fallers.sort_by(|a, b| a.change().cmp(&b.change()).then_with(|| a.previous_rank.cmp(&b.previous_rank)));
+    fallers.truncate(MAX_MOVES);
+
+    let champion_streaks: Vec<(String, usize)> = current.iter()
+        .take_while(|x| x.rank == 1)
+        .map(|entry| {
+            let cmdr = CmdrName::new(&entry.tally.cmdr);
+            let streak = weeks.iter().take_while(|week| find(week, &cmdr).map(|x| x.rank == 1).unwrap_or(false)).count();
+            (entry.tally.cmdr.clone(), streak)
+        })
+        .filter(|(_, streak)| *streak >= 2)
+        .collect();
+
+    return Some(WeekComparison {
+        total_kills: total_kills(current),
+        previous_total_kills: total_kills(previous),
+        new_in_top,
+        climbers,
+        fallers,
+        champion_streaks
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::other::weekly_summary::{CmdrTally, RankedTally};
+
+    use super::{compare_weeks, RankMove};
+
+    /// A leaderboard from `(cmdr, kills)`, ranked in the given order.
+    fn week(entries: &[(&str, u32)]) -> Vec<RankedTally> {
+        let mut result: Vec<RankedTally> = vec![];
+        for (index, (cmdr, kills)) in entries.iter().enumerate() {
+            let rank = match result.last() {
+                Some(previous) if previous.tally.kills == *kills => previous.rank,
+                _ => index as u32 + 1
+            };
+            result.push(RankedTally { rank, tally: CmdrTally { cmdr: cmdr.to_string(), kills: *kills, deaths: 0, last_kill_at: None, last_death_at: None } });
+        }
+        return result;
+    }
+
+    fn rank_move(cmdr: &str, previous_rank: u32, rank: u32) -> RankMove {
+        return RankMove { cmdr: cmdr.to_string(), previous_rank, rank: Some(rank) };
+    }
+
+    fn dropped_off(cmdr: &str, previous_rank: u32) -> RankMove {
+        return RankMove { cmdr: cmdr.to_string(), previous_rank, rank: None };
+    }
+
+    #[test]
+    fn totals_new_entries_and_moves() {
+        let current = week(&[("Alpha", 10), ("Delta", 8), ("Bravo", 5), ("Echo", 2)]);
+        let previous = week(&[("Alpha", 7), ("Bravo", 6), ("Charlie", 4), ("Foxtrot", 3), ("delta", 1)]);
+        let comparison = compare_weeks(&[current, previous], 3).unwrap();
+
+        assert_eq!((comparison.total_kills, comparison.previous_total_kills), (25, 21));
+        assert_eq!(comparison.total_kills_change(), 4);
+        assert_eq!(comparison.new_in_top, vec![("Delta".to_string(), 2)]);
+        assert_eq!(comparison.climbers, vec![rank_move("Delta", 5, 2)]);
+        // Foxtrot dropped off too, but was not in the top before
+        assert_eq!(comparison.fallers, vec![dropped_off("Charlie", 3), rank_move("Bravo", 2, 3)]);
+        assert_eq!(comparison.champion_streaks, vec![("Alpha".to_string(), 2)]);
+    }
+
+    #[test]
+    fn champion_streaks_count_consecutive_weeks() {
+        let weeks = [
+            week(&[("Alpha", 10), ("Bravo", 10)]),
+            week(&[("ALPHA", 9), ("Bravo", 3)]),
+            week(&[("Alpha", 9), ("Bravo", 9)]),
+            week(&[("Bravo", 9), ("Alpha", 3)]),
+        ];
+        let comparison = compare_weeks(&weeks, 10).unwrap();
+        assert_eq!(comparison.champion_streaks, vec![("Alpha".to_string(), 3)]);
+    }
+
+    #[test]
+    fn needs_a_previous_week() {
+        assert_eq!(compare_weeks(&[week(&[("Alpha", 1)])], 10), None);
+        let comparison = compare_weeks(&[week(&[("Alpha", 1)]), vec![]], 10).unwrap();
+        assert_eq!(comparison.previous_total_kills, 0);
+        assert_eq!(comparison.new_in_top, vec![("Alpha".to_string(), 1)]);
+    }
+
+    #[test]
+    fn dropping_off_is_the_biggest_fall() {
+        let current = week(&[("Charlie", 9), ("Delta", 8), ("Alpha", 1)]);
+        let previous = week(&[("Alpha", 10), ("Bravo", 9), ("Echo", 8), ("Charlie", 1)]);
+        let comparison = compare_weeks(&[current, previous], 10).unwrap();
+        assert_eq!(comparison.fallers, vec![dropped_off("Bravo", 2), dropped_off("Echo", 3), rank_move("Alpha", 1, 3)]);
+        assert_eq!(comparison.climbers, vec![rank_move("Charlie", 4, 1)]);
+        assert_eq!(comparison.fallers[0].change(), None);
+    }
+}
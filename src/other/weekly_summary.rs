@@ -1,18 +1,12 @@
-use std::{ops::{Add, Sub}, collections::HashMap};
-use chrono::{Datelike, Timelike, DateTime, Utc, Days};
-use reqwest::StatusCode;
-use serde::{Serialize, Deserialize};
+use std::{cmp::Ordering, ops::Add, collections::HashMap};
+use chrono::{DateTime, Utc, Days};
 
-pub async fn get_kills_for_current_cycle() -> Result<Vec<RangeResponseEntry>, String> {
-    let (start, end) = get_relevant_time_range();
+use crate::{data::Environment, data_server::models::RangeResponseEntry, kill_cache::{KillCacheError, sync::get_kills_between}, util::cmdr_name::{CmdrName, SpellingCounter}, Data};
 
-    let result_with_potentially_out_of_bounds_events =  fetch_from_server(get_query_string_for_api_call(start, end)).await?;
-    
-    let filtered_result: Vec<RangeResponseEntry> = result_with_potentially_out_of_bounds_events.into_iter()
-        .filter(|x| x.timestamp.ge(&start) && x.timestamp.le(&end)).collect::<Vec<_>>();
+use super::leaderboard_window::LeaderboardWindow;
 
-
-    return Ok(filtered_result);
+pub async fn get_kills_for_window(bot_data: &Data, window: &LeaderboardWindow) -> Result<Vec<RangeResponseEntry>, KillCacheError> {
+    return get_kills_between(&bot_data.kill_cache, &bot_data.data_server, window.start, window.end).await;
 }
 /// The API expects a lower and upper date in YYYY-MM-DD Format.
 /// Note that the lower bound is inclusive, the upper bound exclusive
@@ -26,132 +20,247 @@ pub fn get_query_string_for_api_call(start: DateTime<Utc>, end: DateTime<Utc>) -
 
 }
 
-#[derive(Serialize, Debug, Deserialize)]
-struct GetRangeResponse {
-    success: String,
-    count: u32,
-    kills: Vec<_RangeResponseEntry>
+/// What a leaderboard ranks CMDRs by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum LeaderboardMetric {
+    #[name = "kills"]
+    Kills,
+    #[name = "deaths"]
+    Deaths,
+    #[name = "kd"]
+    KillDeathRatio,
+    #[name = "net"]
+    NetScore
 }
 
-#[derive(Serialize, Debug, Deserialize)]
-struct _RangeResponseEntry {
-    id: u64,
-    timestamp: String,
-    killer_name: String,
-    victim_name: String,
-    location: String
+impl LeaderboardMetric {
+
+    pub fn score(&self, tally: &CmdrTally) -> f64 {
+        return match self {
+            LeaderboardMetric::Kills => f64::from(tally.kills),
+            LeaderboardMetric::Deaths => f64::from(tally.deaths),
+            // Without any deaths, the K/D is the amount of kills
+            LeaderboardMetric::KillDeathRatio => f64::from(tally.kills) / f64::from(tally.deaths.max(1)),
+            LeaderboardMetric::NetScore => f64::from(tally.kills) - f64::from(tally.deaths)
+        };
+    }
+
+    pub fn format_score(&self, tally: &CmdrTally) -> String {
+        return match self {
+            LeaderboardMetric::Kills => tally.kills.to_string(),
+            LeaderboardMetric::Deaths => tally.deaths.to_string(),
+            LeaderboardMetric::KillDeathRatio => format!("{:.2}", self.score(tally)),
+            LeaderboardMetric::NetScore => format!("{:+}", i64::from(tally.kills) - i64::from(tally.deaths))
+        };
+    }
+
+    /// Whether a CMDR shows up on this leaderboard at all.
+    /// The K/D leaderboard requires a minimum amount of engagements, otherwise a single lucky kill tops it.
+    pub fn is_eligible(&self, tally: &CmdrTally) -> bool {
+        return match self {
+            LeaderboardMetric::Kills => tally.kills > 0,
+            LeaderboardMetric::Deaths => tally.deaths > 0,
+            LeaderboardMetric::KillDeathRatio => tally.kills + tally.deaths >= Environment::kd_min_engagements(),
+            LeaderboardMetric::NetScore => true
+        };
+    }
+
+    /// When the CMDR reached their final score: their last kill, last death, or last engagement of either kind.
+    /// Used to break ties, whoever got there first ranks higher.
+    pub fn reached_at(&self, tally: &CmdrTally) -> DateTime<Utc> {
+        let reached_at = match self {
+            LeaderboardMetric::Kills => tally.last_kill_at,
+            LeaderboardMetric::Deaths => tally.last_death_at,
+            LeaderboardMetric::KillDeathRatio | LeaderboardMetric::NetScore => tally.last_kill_at.max(tally.last_death_at)
+        };
+        return reached_at.unwrap_or(DateTime::<Utc>::MAX_UTC);
+    }
+
+    /// Order of two CMDRs on this leaderboard, best first:
+    /// 1. higher score
+    /// 2. reached that score earlier, see [LeaderboardMetric::reached_at]
+    /// 3. CMDR name, case insensitive
+    pub fn compare(&self, a: &CmdrTally, b: &CmdrTally) -> Ordering {
+        return self.score(b).total_cmp(&self.score(a))
+            .then_with(|| self.reached_at(a).cmp(&self.reached_at(b)))
+            .then_with(|| CmdrName::new(&a.cmdr).key().cmp(CmdrName::new(&b.cmdr).key()))
+            .then_with(|| a.cmdr.cmp(&b.cmdr));
+    }
+
+    pub fn column_header(&self) -> &'static str {
+        return match self {
+            LeaderboardMetric::Kills => "×:dagger:",
+            LeaderboardMetric::Deaths => "×:skull:",
+            LeaderboardMetric::KillDeathRatio => ":dagger:/:skull:",
+            LeaderboardMetric::NetScore => ":dagger:−:skull:"
+        };
+    }
+
+    pub fn title(&self) -> &'static str {
+        return match self {
+            LeaderboardMetric::Kills => "Most Kills",
+            LeaderboardMetric::Deaths => "Most Deaths",
+            LeaderboardMetric::KillDeathRatio => "Best K/D",
+            LeaderboardMetric::NetScore => "Best Net Score"
+        };
+    }
 }
 
-#[derive(Serialize, Debug, Deserialize)]
-pub struct RangeResponseEntry {
-    id: u64,
-    timestamp: DateTime<Utc>,
-    killer_name: String,
-    victim_name: String,
-    location: Option<String>
+/// Kills and deaths of a single CMDR within a set of kills. `cmdr` is the most common spelling of their name, see [CmdrName].
+#[derive(Debug, Clone)]
+pub struct CmdrTally {
+    pub cmdr: String,
+    pub kills: u32,
+    pub deaths: u32,
+    pub last_kill_at: Option<DateTime<Utc>>,
+    pub last_death_at: Option<DateTime<Utc>>
 }
 
-impl _RangeResponseEntry {
-    fn convert(self: Self) -> RangeResponseEntry {
-
-        let id = self.id;
-        let timestamp: DateTime<Utc> = match DateTime::parse_from_str(format!("{} +00:00", &self.timestamp).as_str(), "%Y-%m-%d %H:%M:%S %z") {
-            Ok(data) => data.with_timezone(&Utc),
-            Err(err) => {
-                println!("[ERROR]: Failed to parse Timestamp. Using Utc.now as fallback. Error below:\n{err}");
-                Utc::now()
-            },
-        }; 
-        let location: Option<String> = match self.location.eq_ignore_ascii_case("UNKNOWN") {
-            true => None,
-            false => Some(self.location)
-        };
-        let killer_name = self.killer_name;
-        let victim_name = self.victim_name;
-        return RangeResponseEntry {
-            id,
-            timestamp,
-            killer_name,
-            victim_name,
-            location
-        }
+impl CmdrTally {
+    fn new(cmdr: &str) -> Self {
+        return CmdrTally { cmdr: cmdr.to_owned(), kills: 0, deaths: 0, last_kill_at: None, last_death_at: None };
     }
 }
 
+/// A CMDR on a leaderboard. CMDRs with the same score share a rank, the next rank is skipped (1, 2, 2, 4).
+#[derive(Debug, Clone)]
+pub struct RankedTally {
+    pub rank: u32,
+    pub tally: CmdrTally
+}
 
-pub async fn fetch_from_server(query_string: String) -> Result<Vec<RangeResponseEntry>, String> {
-    let server_url = crate::data::Environment::server_address();
-    let server_auth = crate::data::Environment::server_auth();
+pub fn get_tallies(data: &[RangeResponseEntry]) -> Vec<CmdrTally> {
+    let mut summary_map: HashMap<CmdrName, (CmdrTally, SpellingCounter)> = HashMap::new();
 
+    for x in data {
+        let killer_name = CmdrName::new(&x.killer_name);
+        let (killer, spellings) = summary_map.entry(killer_name.clone()).or_insert_with(|| (CmdrTally::new(killer_name.display()), SpellingCounter::default()));
+        spellings.add(&killer_name);
+        killer.kills += 1;
+        killer.last_kill_at = killer.last_kill_at.max(Some(x.timestamp));
 
-    async fn run_request(path: String, server_auth: String) -> Result<Option<Vec<_RangeResponseEntry>>, reqwest::Error> {
-        let response = reqwest::Client::new()
-            .get(path).bearer_auth(server_auth).send().await?;
+        let victim_name = CmdrName::new(&x.victim_name);
+        let (victim, spellings) = summary_map.entry(victim_name.clone()).or_insert_with(|| (CmdrTally::new(victim_name.display()), SpellingCounter::default()));
+        spellings.add(&victim_name);
+        victim.deaths += 1;
+        victim.last_death_at = victim.last_death_at.max(Some(x.timestamp));
+    }
 
-        if response.status() != StatusCode::OK {
-            return Ok(None);
+    return summary_map.into_values().map(|(mut tally, spellings)| {
+        if let Some(spelling) = spellings.most_common() {
+            tally.cmdr = spelling;
         }
-        let parsed_response: GetRangeResponse = response.json::<GetRangeResponse>().await?;
-        return Ok(Some(parsed_response.kills))
-    }
+        tally
+    }).collect();
+}
 
-    match run_request(format!("{server_url}/killboard/get/kills{query_string}"), server_auth).await {
-        Ok(data) => match data {
-            Some(e) => {
-                let mapped_data = e.into_iter().map(|x| x.convert()).collect::<Vec<_>>();
-                Ok(mapped_data)
-            },
-            None => Err("Failed to receive any data from Range Query".to_string())
-        },
-        Err(err) => Err(err.to_string()),
-    }
+/// All CMDRs eligible for the leaderboard of `metric`, best first, in the order of [LeaderboardMetric::compare].
+/// Ranks follow standard competition ranking, only the score decides whether two CMDRs share a rank.
+pub fn get_sorted_summary(data: &[RangeResponseEntry], metric: LeaderboardMetric) -> Vec<RankedTally> {
+    let mut tallies: Vec<CmdrTally> = get_tallies(data).into_iter().filter(|x| metric.is_eligible(x)).collect();
+    tallies.sort_by(|a, b| metric.compare(a, b));
 
+    let mut result: Vec<RankedTally> = Vec::with_capacity(tallies.len());
+    for (index, tally) in tallies.into_iter().enumerate() {
+        let rank = match result.last() {
+            Some(previous) if metric.score(&previous.tally) == metric.score(&tally) => previous.rank,
+            _ => u32::try_from(index).unwrap() + 1
+        };
+        result.push(RankedTally { rank, tally });
+    }
+    return result;
 }
 
-pub fn get_relevant_time_range() -> (DateTime<Utc>, DateTime<Utc>) {
-    let now = chrono::offset::Utc::now();
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
 
-    let offset_to_end = match now.weekday() {
-        chrono::Weekday::Mon => 4,
-        chrono::Weekday::Tue => 5,
-        chrono::Weekday::Wed => 6,
-        chrono::Weekday::Thu => 0,
-        chrono::Weekday::Fri => 1,
-        chrono::Weekday::Sat => 2,
-        chrono::Weekday::Sun => 3,
-    };
+    use crate::{data_server::models::RangeResponseEntry, test_support};
 
-    let mut start = if offset_to_end == 0 && now.hour() < 8 {
-        now.sub(Days::new(7))
-    } else { 
-        now.sub(Days::new(offset_to_end))
-    };
+    use super::{get_sorted_summary, LeaderboardMetric};
 
-    fn override_hours_and_minutes_to_0800(date: DateTime<Utc>) -> Option<DateTime<Utc>> {
-        return date.with_hour(8)?.with_minute(0)?.with_second(0)?.with_nanosecond(0)
+    fn kill(id: u64, minute: u32, killer: &str, victim: &str) -> RangeResponseEntry {
+        return test_support::kill(id, Utc.with_ymd_and_hms(2023, 12, 14, 12, minute, 0).unwrap(), killer, victim);
     }
 
-    match override_hours_and_minutes_to_0800(start) {
-        Some(data) => start = data,
-        None => println!("[WARN] Failed to create correct timestamp for finding relevant range"),
+    fn ranking(data: &[RangeResponseEntry], metric: LeaderboardMetric) -> Vec<(u32, String)> {
+        return get_sorted_summary(data, metric).into_iter().map(|x| (x.rank, x.tally.cmdr)).collect();
     }
 
-    let end = start.add(Days::new(7));
+    #[test]
+    fn equal_scores_share_a_rank_and_skip_the_next() {
+        let data = [
+            kill(1, 0, "Alpha", "Victim"),
+            kill(2, 1, "Alpha", "Victim"),
+            kill(3, 2, "Bravo", "Victim"),
+            kill(4, 3, "Charlie", "Victim"),
+            kill(5, 4, "Delta", "Victim"),
+            kill(6, 5, "Delta", "Victim"),
+            kill(7, 6, "Delta", "Victim"),
+        ];
+        assert_eq!(ranking(&data, LeaderboardMetric::Kills), vec![
+            (1, "Delta".to_string()),
+            (2, "Alpha".to_string()),
+            (3, "Bravo".to_string()),
+            (3, "Charlie".to_string()),
+        ]);
+    }
 
-    return (start, end)
-}
+    #[test]
+    fn ties_go_to_whoever_reached_the_score_first() {
+        let data = [
+            kill(1, 0, "Zulu", "Victim"),
+            kill(2, 1, "Alpha", "Victim"),
+            kill(3, 2, "Alpha", "Victim"),
+            kill(4, 3, "Zulu", "Victim"),
+        ];
+        // Both have 2 kills, Alpha got the second one first
+        assert_eq!(ranking(&data, LeaderboardMetric::Kills), vec![(1, "Alpha".to_string()), (1, "Zulu".to_string())]);
+    }
 
-pub fn get_sorted_weekly_summary(data: Vec<RangeResponseEntry>) -> Vec<(String, u32)> {
-    let mut summary_map: HashMap<String, u32> = HashMap::new();
+    #[test]
+    fn ties_at_the_same_time_are_ordered_by_name() {
+        let data = [
+            kill(1, 0, "charlie", "Victim"),
+            kill(2, 0, "Bravo", "Victim"),
+            kill(3, 0, "alpha", "Victim"),
+        ];
+        assert_eq!(ranking(&data, LeaderboardMetric::Kills), vec![
+            (1, "alpha".to_string()),
+            (1, "Bravo".to_string()),
+            (1, "charlie".to_string()),
+        ]);
+    }
 
-    data.iter().for_each(|x| {
-        if !summary_map.contains_key(&x.killer_name) {
-            summary_map.insert(x.killer_name.to_owned(), 0);
-        }
-        summary_map.insert(x.killer_name.to_owned(), summary_map.get(&x.killer_name).unwrap()+1);
-    });
+    #[test]
+    fn order_does_not_depend_on_input_order() {
+        let mut data = vec![
+            kill(1, 0, "Alpha", "Bravo"),
+            kill(2, 1, "Bravo", "Charlie"),
+            kill(3, 2, "Charlie", "Alpha"),
+            kill(4, 3, "Delta", "Echo"),
+        ];
+        let expected = ranking(&data, LeaderboardMetric::NetScore);
+        data.reverse();
+        assert_eq!(ranking(&data, LeaderboardMetric::NetScore), expected);
+        assert_eq!(expected.iter().map(|x| x.0).collect::<Vec<_>>(), vec![1, 2, 2, 2, 5]);
+    }
+
+    #[test]
+    fn spellings_of_the_same_cmdr_are_merged() {
+        let data = [
+            kill(1, 0, "phDeez", "Victim"),
+            kill(2, 1, "PHDEEZ", "victim "),
+            kill(3, 2, "CMDR phDeez", "Victim"),
+            kill(4, 3, "Other", "phdeez"),
+        ];
+        let kills = get_sorted_summary(&data, LeaderboardMetric::Kills);
+        assert_eq!(kills.len(), 2);
+        assert_eq!(kills[0].tally.cmdr, "phDeez");
+        assert_eq!(kills[0].tally.kills, 3);
+        assert_eq!(kills[0].tally.deaths, 1);
 
-    let mut as_tuple_vec: Vec<(String, u32)>  =  summary_map.iter().map(|(k,v)| { (k.to_owned(),v.to_owned())}).collect::<_>();
-    as_tuple_vec.sort_by(|a,b| {b.1.cmp(&a.1) });
-    as_tuple_vec
-}
\ No newline at end of file
+        // A spelling only seen once does not win over the common one
+        assert_eq!(ranking(&data, LeaderboardMetric::Deaths), vec![(1, "Victim".to_string()), (2, "phDeez".to_string())]);
+    }
+}
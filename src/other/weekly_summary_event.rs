@@ -1,47 +1,172 @@
-use poise::serenity_prelude::{Http, ChannelId, Color};
-use std::ops::{Add, Sub};
-use chrono::{Datelike, Timelike, DateTime, Utc, Days, NaiveDate, NaiveDateTime, NaiveTime};
+use poise::serenity_prelude::{Http, ChannelId, Color, AttachmentType, CreateEmbed};
+use std::{borrow::Cow, time::Instant};
+use chrono::{DateTime, Utc, NaiveDate};
 
-use crate::{data::{self, Environment}, commands::pvpweekly::DataRow};
+use crate::{charts::bar_chart, data::{self, Environment}, commands::pvpweekly::DataRow, util::cmdr_name::CmdrName, data_server::models::RangeResponseEntry, kill_cache::{sync::{get_kills_between, sync_once}, KillCacheError}, Data};
 
-use super::weekly_summary::{fetch_from_server, get_query_string_for_api_call, get_sorted_weekly_summary};
+use super::{weekly_summary::{get_sorted_summary, RankedTally, LeaderboardMetric}, leaderboard_window::{LeaderboardWindow, WindowAnchor}, trends::last_windows, week_comparison::{compare_weeks, RankMove, WeekComparison}};
 
-pub async fn subscribe_for_event(date_override: Option<NaiveDate> ) -> Result<(), String> {
-    let client = Http::new(&data::Environment::discord_token());
+/// Weeks the summary compares, including the week it reports on. Also the longest #1 streak that can be detected.
+const COMPARISON_WEEKS: usize = 8;
 
-    let relevant_range = get_relevant_time_range_summary(date_override);
-    let data = fetch_from_server(get_query_string_for_api_call(relevant_range.0, relevant_range.1)).await;
+/// Ranks a CMDR has to reach to be in the top of the summary.
+const SUMMARY_TOP: u32 = 10;
 
+pub async fn subscribe_for_event(bot_data: &Data, date_override: Option<NaiveDate> ) -> Result<(), String> {
+    let window = get_relevant_window(date_override);
+    let data = get_kills_of_closing_window(bot_data, &window).await.map_err(|x| x.to_string());
+    let earlier_weeks = get_earlier_weeks(bot_data, &window).await;
 
-    let message_sent_result = ChannelId(Environment::discord_weekly_update_channel_id()).send_message(&client, |m| m.embed(|embed| {
-        embed.title("The leaderboard period has ended.").footer(
-            |footer| footer.text(format!("Current leaderboard period is from {} to {}, starting at 8AM UTC.", relevant_range.0.format("%Y-%m-%d"), relevant_range.1.format("%Y-%m-%d")))  
-        );
-        match data {
-            Err(err) => {
-                embed.color(Color::RED).title("Something went wrong...").description(err);
-                embed
-            },
+    return post_summary(window.as_tuple(), data, earlier_weeks).await;
+}
+
+/// Syncs the cache before reading the kills of `window`, so the summary does not miss kills the background sync has not picked up yet.
+async fn get_kills_of_closing_window(bot_data: &Data, window: &LeaderboardWindow) -> Result<Vec<RangeResponseEntry>, KillCacheError> {
+    sync_once(&bot_data.kill_cache, &bot_data.data_server).await?;
+    return get_kills_between(&bot_data.kill_cache, &bot_data.data_server, window.start, window.end).await;
+}
+
+/// Kill leaderboards of the weeks before `window`, newest first. `None` if they could not be loaded, the summary is then posted without a comparison.
+async fn get_earlier_weeks(bot_data: &Data, window: &LeaderboardWindow) -> Option<Vec<Vec<RankedTally>>> {
+    let windows = last_windows(window.previous(), COMPARISON_WEEKS - 1);
+    let (start, end) = (windows.first().unwrap().start, windows.last().unwrap().end);
+    let data = match get_kills_between(&bot_data.kill_cache, &bot_data.data_server, start, end).await {
+        Ok(data) => data,
+        Err(err) => {
+            println!("[WARN] Weekly summary is posted without a comparison to earlier weeks: {}", err);
+            return None;
+        }
+    };
+
+    // The cache returns the kills oldest first, so every week is a slice of them
+    return Some(windows.iter().rev().map(|window| {
+        let first = data.partition_point(|x| x.timestamp < window.start);
+        let last = data.partition_point(|x| x.timestamp < window.end);
+        get_sorted_summary(&data[first..last], LeaderboardMetric::Kills)
+    }).collect());
+}
+
+/// Entry point for the scheduled Job. Unlike [subscribe_for_event] a failing Data Server does not end up
+/// as an error embed in the public channel. The request is instead repeated until the configured retry window
+/// has passed, after which the admins are pinged so they can use `/pvpadmin_force_weekly_summary`.
+pub async fn run_scheduled_summary(bot_data: &Data) {
+    let window = get_relevant_window(None);
+    let relevant_range = window.as_tuple();
+    let deadline = Instant::now() + Environment::weekly_summary_retry_window();
+    let retry_interval = Environment::weekly_summary_retry_interval();
+
+    loop {
+        match get_kills_of_closing_window(bot_data, &window).await {
             Ok(data) => {
-                let data_len = data.len();
-                if data_len == 0 {
-                    embed.color(Color::RED).description("No kills this week?! Wtf, GGI.");
-                } else {
-                    let result: Vec<(String, u32)> = get_sorted_weekly_summary(data);
-                    let summary = result.into_iter().enumerate().take(10).map(|(i,e)| DataRow {
-                        position: u32::try_from(i).unwrap() + 1,
-                        cmdr: e.0.to_owned(),
-                        kill_count: e.1,
-                        highlight: false
-                    }).collect::<Vec<_>>();
-                    embed.color(Color::DARK_GREEN).description(format!("This week, GGI logged a total of {} kills. Here's the top 10.", data_len));
-
-                    build_weekly_embeds(summary, embed);
+                let earlier_weeks = get_earlier_weeks(bot_data, &window).await;
+                let _ = post_summary(relevant_range, Ok(data), earlier_weeks).await;
+                return;
+            },
+            Err(err) => {
+                if Instant::now() + retry_interval > deadline {
+                    println!("[ERROR] Giving up on the weekly summary: {}", err);
+                    notify_admins_about_failed_summary(relevant_range, err.to_string()).await;
+                    return;
+                }
+                println!("[WARN] Failed to fetch data for the weekly summary, retrying in {}s: {}", retry_interval.as_secs(), err);
+                tokio::time::sleep(retry_interval).await;
+            }
+        }
+    }
+}
+
+async fn notify_admins_about_failed_summary(relevant_range: (DateTime<Utc>, DateTime<Utc>), reason: String) {
+    let client = Http::new(&data::Environment::discord_token());
+
+    let message_sent_result = ChannelId(Environment::discord_admin_channel_id()).send_message(&client, |m| m
+        .content(format!("<@&{}>", Environment::role_admin()))
+        .embed(|embed| embed
+            .color(Color::RED)
+            .title("The weekly summary could not be posted")
+            .description(format!(
+                "The kills for this week could not be loaded for {} minutes. Use `/pvpadmin_force_weekly_summary for_date:{}` once it is back.\n\nLast Error: {}",
+                Environment::weekly_summary_retry_window().as_secs() / 60,
+                relevant_range.1.format("%Y-%m-%d"),
+                reason
+            ))
+        )
+    ).await;
+
+    if let Err(err) = message_sent_result {
+        println!("Failed to notify admins about the failed weekly summary: {}", err);
+    }
+}
+
+async fn post_summary(relevant_range: (DateTime<Utc>, DateTime<Utc>), data: Result<Vec<RangeResponseEntry>, String>, earlier_weeks: Option<Vec<Vec<RankedTally>>>) -> Result<(), String> {
+    let client = Http::new(&data::Environment::discord_token());
+
+    let this_week = match &data {
+        Ok(data) => get_sorted_summary(data, LeaderboardMetric::Kills),
+        Err(_) => Vec::new()
+    };
+    let chart = match &data {
+        Ok(_) => build_top_chart(&this_week, LeaderboardMetric::Kills, 10, "Top 10 of the week"),
+        Err(_) => None
+    };
+    let comparison = match (&data, earlier_weeks) {
+        (Ok(data), Some(earlier_weeks)) if !data.is_empty() => {
+            let mut weeks = vec![this_week.clone()];
+            weeks.extend(earlier_weeks);
+            compare_weeks(&weeks, SUMMARY_TOP)
+        },
+        _ => None
+    };
+
+    let message_sent_result = ChannelId(Environment::discord_weekly_update_channel_id()).send_message(&client, |m| {
+        m.embed(|embed| {
+            embed.title("The leaderboard period has ended.").footer(
+                |footer| footer.text(format!("Current leaderboard period is from {} to {}, starting {}.", relevant_range.0.format("%Y-%m-%d"), relevant_range.1.format("%Y-%m-%d"), WindowAnchor::from_environment()))
+            );
+            match &data {
+                Err(err) => {
+                    embed.color(Color::RED).title("Something went wrong...").description(err);
+                    embed
+                },
+                Ok(data) => {
+                    let data_len = data.len();
+                    if data_len == 0 {
+                        embed.color(Color::RED).description("No kills this week?! Wtf, GGI.");
+                    } else {
+                        let summary = this_week.iter().take(10).map(|e| to_data_row(e, LeaderboardMetric::Kills, false)).collect::<Vec<_>>();
+                        embed.color(Color::DARK_GREEN).description(format!("This week, GGI logged a total of {} kills. Here's the top 10.", data_len));
+
+                        build_weekly_embeds(summary, LeaderboardMetric::Kills, embed);
+                        if chart.is_some() {
+                            embed.attachment(TOP_CHART_FILE);
+                        }
+                    }
+                    embed
                 }
-                embed
             }
+        });
+        if let Some(comparison) = &comparison {
+            m.add_embed(|embed| build_comparison_embed(comparison, embed));
+        }
+        if let Some(chart) = &chart {
+            m.add_file(AttachmentType::Bytes { data: Cow::Borrowed(chart), filename: TOP_CHART_FILE.to_string() });
         }
-    }) ).await;
+
+        // Additional rankings, each in its own embed below the kill leaderboard
+        if let Ok(data) = &data {
+            for metric in [LeaderboardMetric::Deaths, LeaderboardMetric::KillDeathRatio, LeaderboardMetric::NetScore] {
+                let result = get_sorted_summary(data, metric);
+                if result.is_empty() {
+                    continue;
+                }
+                let summary = result.iter().take(5).map(|e| to_data_row(e, metric, false)).collect::<Vec<_>>();
+                m.add_embed(|embed| {
+                    build_weekly_embeds(summary, metric, embed);
+                    embed.title(metric.title())
+                });
+            }
+        }
+        m
+    }).await;
 
     return match message_sent_result {
         Ok(_) => {
@@ -57,68 +182,157 @@ pub async fn subscribe_for_event(date_override: Option<NaiveDate> ) -> Result<()
     };
 }
 
-fn get_relevant_time_range_summary(date_override: Option<NaiveDate>) -> (DateTime<Utc>, DateTime<Utc>) {
-    let date = match date_override {
-        None => chrono::offset::Utc::now(),
-        Some(e) => NaiveDateTime::new(e, NaiveTime::from_num_seconds_from_midnight_opt(0,0).unwrap()).and_utc()
+/// The window a summary is posted for. The scheduled summary reports the window that just ended.
+/// With a date override, it is the window that ended most recently by that date's anchor hour.
+fn get_relevant_window(date_override: Option<NaiveDate>) -> LeaderboardWindow {
+    return match date_override {
+        None => LeaderboardWindow::current().previous(),
+        Some(date) => LeaderboardWindow::ended_by(date, WindowAnchor::from_environment())
     };
+}
 
-    let offset_to_end = match date.weekday() {
-        chrono::Weekday::Mon => 4,
-        chrono::Weekday::Tue => 5,
-        chrono::Weekday::Wed => 6,
-        chrono::Weekday::Thu => 7,
-        chrono::Weekday::Fri => 1,
-        chrono::Weekday::Sat => 2,
-        chrono::Weekday::Sun => 3,
+fn build_comparison_embed<'a>(comparison: &WeekComparison, embed: &'a mut CreateEmbed) -> &'a mut CreateEmbed {
+    let change = comparison.total_kills_change();
+    let trend = match change {
+        x if x > 0 => ":chart_with_upwards_trend:",
+        x if x < 0 => ":chart_with_downwards_trend:",
+        _ => ":heavy_minus_sign:"
     };
+    let mut lines = vec![match comparison.previous_total_kills {
+        0 => format!("{} {} kills, none the week before.", trend, comparison.total_kills),
+        previous => format!(
+            "{} {} kills, {:+} ({:+.0}%) compared to {} the week before.",
+            trend, comparison.total_kills, change, change as f64 * 100.0 / f64::from(previous), previous
+        )
+    }];
 
-    let mut start = date.sub(Days::new(offset_to_end));
-
-    fn override_hours_and_minutes_to_0800(date: DateTime<Utc>) -> Option<DateTime<Utc>> {
-        return date.with_hour(8)?.with_minute(0)?.with_second(0)?.with_nanosecond(0)
+    if !comparison.new_in_top.is_empty() {
+        let entries: Vec<String> = comparison.new_in_top.iter().map(|(cmdr, rank)| format!("{} (#{})", cmdr, rank)).collect();
+        lines.push(format!("**New in the top {}:** {}", SUMMARY_TOP, entries.join(", ")));
     }
-
-    match override_hours_and_minutes_to_0800(start) {
-        Some(data) => start = data,
-        None => println!("[WARN] Failed to create correct timestamp for finding relevant range"),
+    let format_moves = |moves: &[RankMove]| moves.iter()
+        .map(|x| match (x.rank, x.change()) {
+            (Some(rank), Some(change)) => format!("{} #{} → #{} ({:+})", x.cmdr, x.previous_rank, rank, change),
+            _ => format!("{} #{} → no kills", x.cmdr, x.previous_rank)
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    if !comparison.climbers.is_empty() {
+        lines.push(format!("**Climbers:** {}", format_moves(&comparison.climbers)));
+    }
+    if !comparison.fallers.is_empty() {
+        lines.push(format!("**Fallers:** {}", format_moves(&comparison.fallers)));
+    }
+    for (cmdr, streak) in &comparison.champion_streaks {
+        let weeks = match *streak >= COMPARISON_WEEKS {
+            true => format!("{}+", streak),
+            false => streak.to_string()
+        };
+        lines.push(format!(":crown: CMDR {} is #1 for {} weeks running.", cmdr, weeks));
     }
 
-    let end = start.add(Days::new(7));
-
-    return (start, end)
+    return embed.color(Color::DARK_GREEN).title("Compared to the week before").description(lines.join("\n"));
 }
 
 
 
-pub fn build_weekly_embeds(data: Vec<DataRow>, embed: &mut poise::serenity_prelude::CreateEmbed) {
+pub fn build_weekly_embeds(data: Vec<DataRow>, metric: LeaderboardMetric, embed: &mut poise::serenity_prelude::CreateEmbed) {
     embed.color(Color::DARK_GREEN).title("Result for current leaderboard week");
 
     let mut position_row: Vec<String> = vec![];
     let mut cmdr_row: Vec<String> = vec![];
-    let mut count_row: Vec<String> = vec![];
+    let mut score_row: Vec<String> = vec![];
 
     for entry in data {
         match entry.highlight {
             true => {
                 position_row.push(format!("**{}**", entry.position));
                 cmdr_row.push(format!("**{}**", entry.cmdr));
-                count_row.push(format!("**{}**", entry.kill_count));
+                score_row.push(format!("**{}**", entry.score));
             }
             false => {
                 position_row.push(format!("{}", entry.position));
-                cmdr_row.push(format!("{}", entry.cmdr));
-                count_row.push(format!("{}", entry.kill_count));
+                cmdr_row.push(entry.cmdr.to_string());
+                score_row.push(entry.score.to_string());
             }
         }
     }
 
     embed.field(":trophy:", position_row.join("\n"), true)
         .field(":busts_in_silhouette:", cmdr_row.join("\n"), true)
-        .field("×:dagger:", count_row.join("\n"), true);
+        .field(metric.column_header(), score_row.join("\n"), true);
+}
+
+/// File name the leaderboard chart is attached as, see [build_top_chart].
+pub const TOP_CHART_FILE: &str = "leaderboard.png";
+
+/// Bar chart of the first `count` CMDRs of a leaderboard.
+/// `None` if there is nothing to draw or drawing failed, the leaderboard is complete without it.
+pub fn build_top_chart(result: &[RankedTally], metric: LeaderboardMetric, count: usize, title: &str) -> Option<Vec<u8>> {
+    if result.is_empty() {
+        return None;
+    }
+    let bars: Vec<(String, f64)> = result.iter()
+        .take(count)
+        .map(|x| (format!("{}. {}", x.rank, x.tally.cmdr), metric.score(&x.tally)))
+        .collect();
+    return match bar_chart(title, &bars) {
+        Ok(chart) => Some(chart),
+        Err(err) => {
+            println!("[WARN] Leaderboard is posted without a chart: {}", err);
+            None
+        }
+    };
 }
 
+fn to_data_row(entry: &RankedTally, metric: LeaderboardMetric, highlight: bool) -> DataRow {
+    return DataRow {
+        position: entry.rank,
+        cmdr: entry.tally.cmdr.to_owned(),
+        score: metric.format_score(&entry.tally),
+        highlight
+    };
+}
 
-pub fn is_cmdr_in_result_set(data: &Vec<(String, u32)>, cmdr: &String) -> bool {
-    return data.iter().find(|&x| x.0.eq_ignore_ascii_case(&cmdr)).is_some();
-}
\ No newline at end of file
+/// Rows to show for a leaderboard. Without a CMDR this is the top 10.
+/// With a CMDR it is the CMDR (highlighted) and up to 4 CMDRs ranked directly above, or `None` if the CMDR is not on the leaderboard.
+pub fn get_rows_to_present(result: Vec<RankedTally>, metric: LeaderboardMetric, cmdr_name: Option<&str>) -> Option<Vec<DataRow>> {
+    return match cmdr_name {
+        Some(name) => {
+            let name = CmdrName::new(name);
+            let cmdr_index = result.iter().position(|x| name.matches(&x.tally.cmdr))?;
+            let first_index = cmdr_index.saturating_sub(4);
+
+            let data = result[first_index..=cmdr_index].iter().enumerate()
+                .map(|(i, e)| to_data_row(e, metric, first_index + i == cmdr_index))
+                .collect::<Vec<_>>();
+
+            Some(data)
+        },
+        None => {
+            let data = result.iter().take(10).map(|e| to_data_row(e, metric, false)).collect::<Vec<_>>();
+            Some(data)
+        },
+    };
+}
+
+/// The top `count` rows. If a CMDR is given, their row is highlighted, and appended below the top rows if they did not make it.
+/// Returns whether the CMDR was found at all.
+pub fn get_top_rows_with_highlight(result: Vec<RankedTally>, metric: LeaderboardMetric, count: usize, cmdr_name: Option<&str>) -> (Vec<DataRow>, bool) {
+    let cmdr_name = cmdr_name.map(CmdrName::new);
+    let is_cmdr = |name: &str| cmdr_name.as_ref().map(|x| x.matches(name)).unwrap_or(false);
+
+    let mut found = false;
+    let mut rows: Vec<DataRow> = vec![];
+    for (i, entry) in result.iter().enumerate() {
+        let highlight = is_cmdr(&entry.tally.cmdr);
+        found |= highlight;
+        if i < count || highlight {
+            rows.push(to_data_row(entry, metric, highlight));
+        }
+        if i >= count && (found || cmdr_name.is_none()) {
+            break;
+        }
+    }
+    return (rows, found);
+}
@@ -0,0 +1,33 @@
+//! Builders for test fixtures that are needed by more than one module.
+
+use chrono::{DateTime, Utc};
+
+use crate::{data_server::models::RangeResponseEntry, other::history::{HistoryEntry, HistoryEventType}};
+
+/// A kill without a location or ships. Use struct update syntax to set those.
+pub fn kill(id: u64, timestamp: DateTime<Utc>, killer: &str, victim: &str) -> RangeResponseEntry {
+    return RangeResponseEntry {
+        id,
+        timestamp,
+        killer_name: killer.to_string(),
+        victim_name: victim.to_string(),
+        location: None,
+        killer_ship: None,
+        victim_ship: None
+    };
+}
+
+/// An engagement against `other_cmdr` at a `YYYY-MM-DD HH:MM:SS` timestamp, in a Mamba against a Python with both sides Elite.
+/// Use struct update syntax to change the rest.
+pub fn history_entry(event_type: HistoryEventType, other_cmdr: &str, timestamp: &str) -> HistoryEntry {
+    return HistoryEntry {
+        event_type,
+        other_cmdr: other_cmdr.to_string(),
+        timestamp: timestamp.to_string(),
+        location: None,
+        own_ship: "mamba".to_string(),
+        own_rank: "8".to_string(),
+        other_ship: "python".to_string(),
+        other_rank: "8".to_string()
+    };
+}
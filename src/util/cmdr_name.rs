@@ -0,0 +1,192 @@
+use std::{collections::HashMap, fmt::Display, hash::{Hash, Hasher}};
+
+/// Prefix some players and tools put in front of the name. It is not part of the name itself.
+const CMDR_PREFIX: &str = "cmdr ";
+
+/// The name of a CMDR as the identity of a player.
+/// The killboard, its users and the game itself do not agree on the spelling of a name, so two names are the same CMDR if their
+/// normalized forms are equal. Normalizing trims surrounding whitespace, strips an optional "CMDR " prefix and folds the case.
+/// The spelling is kept for display.
+#[derive(Debug, Clone)]
+pub struct CmdrName {
+    display: String,
+    key: String
+}
+
+impl CmdrName {
+
+    pub fn new(raw: &str) -> Self {
+        let display = strip_prefix(raw.trim()).to_string();
+        let key = display.to_lowercase();
+        return CmdrName { display, key };
+    }
+
+    /// The name for comparisons. Do not show this to users.
+    pub fn key(&self) -> &str {
+        return &self.key;
+    }
+
+    pub fn display(&self) -> &str {
+        return &self.display;
+    }
+
+    /// Whether `raw` is a spelling of this CMDR.
+    pub fn matches(&self, raw: &str) -> bool {
+        return CmdrName::new(raw).key == self.key;
+    }
+}
+
+/// "CMDR Foo" becomes "Foo". A CMDR actually called "CMDR" keeps their name.
+fn strip_prefix(name: &str) -> &str {
+    if name.len() > CMDR_PREFIX.len() && name.is_char_boundary(CMDR_PREFIX.len()) && name[..CMDR_PREFIX.len()].eq_ignore_ascii_case(CMDR_PREFIX) {
+        return name[CMDR_PREFIX.len()..].trim_start();
+    }
+    return name;
+}
+
+impl PartialEq for CmdrName {
+    fn eq(&self, other: &Self) -> bool {
+        return self.key == other.key;
+    }
+}
+
+impl Eq for CmdrName {}
+
+impl Hash for CmdrName {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+    }
+}
+
+impl Display for CmdrName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.display)
+    }
+}
+
+/// Counts the spellings a CMDR was seen with, to display the one used most.
+#[derive(Debug, Clone, Default)]
+pub struct SpellingCounter {
+    counts: HashMap<String, u32>
+}
+
+impl SpellingCounter {
+
+    pub fn add(&mut self, name: &CmdrName) {
+        *self.counts.entry(name.display().to_string()).or_insert(0) += 1;
+    }
+
+    /// The most common spelling. On a tie the spelling that sorts first wins, so the result does not depend on the order kills came in.
+    pub fn most_common(&self) -> Option<String> {
+        return self.counts.iter()
+            .max_by(|(a_name, a_count), (b_name, b_count)| a_count.cmp(b_count).then_with(|| b_name.cmp(a_name)))
+            .map(|(name, _)| name.to_owned());
+    }
+}
+
+/// Number of single character insertions, deletions or substitutions to turn `a` into `b`.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous_row: Vec<usize> = (0..=b.len()).collect();
+    let mut current_row: Vec<usize> = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.chars().enumerate() {
+        current_row[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution_cost = if a_char == *b_char { 0 } else { 1 };
+            current_row[j + 1] = (previous_row[j] + substitution_cost)
+                .min(previous_row[j + 1] + 1)
+                .min(current_row[j] + 1);
+        }
+        std::mem::swap(&mut previous_row, &mut current_row);
+    }
+    return previous_row[b.len()];
+}
+
+/// Up to `limit` of `candidates` that are probably what the user meant when typing `input`, closest first.
+/// Only names within a few typos of the input are considered, a third of its length but at least 2.
+/// Each CMDR is only suggested once, in the first spelling found.
+pub fn closest_names<'a>(input: &str, candidates: impl IntoIterator<Item = &'a str>, limit: usize) -> Vec<String> {
+    let input = CmdrName::new(input);
+    let max_distance = (input.key().chars().count() / 3).max(2);
+
+    let mut matches: HashMap<String, (usize, String)> = HashMap::new();
+    for candidate in candidates {
+        let candidate = CmdrName::new(candidate);
+        if candidate.key().is_empty() || candidate == input {
+            continue;
+        }
+        let distance = edit_distance(input.key(), candidate.key());
+        if distance <= max_distance {
+            matches.entry(candidate.key().to_string()).or_insert((distance, candidate.display().to_string()));
+        }
+    }
+
+    let mut matches: Vec<(usize, String)> = matches.into_values().collect();
+    matches.sort();
+    return matches.into_iter().take(limit).map(|(_, name)| name).collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{closest_names, edit_distance, CmdrName, SpellingCounter};
+
+    #[test]
+    fn normalizes_case_whitespace_and_prefix() {
+        let name = CmdrName::new("  CMDR phDeez ");
+        assert_eq!(name.display(), "phDeez");
+        assert_eq!(name.key(), "phdeez");
+        assert_eq!(name, CmdrName::new("PHDEEZ"));
+        assert_eq!(name, CmdrName::new("cmdr  phdeez"));
+        assert!(name.matches("Cmdr PhDeez"));
+        assert!(!name.matches("phDeez2"));
+    }
+
+    #[test]
+    fn a_cmdr_called_cmdr_keeps_their_name() {
+        assert_eq!(CmdrName::new("CMDR").display(), "CMDR");
+        assert_eq!(CmdrName::new("CMDR ").display(), "CMDR");
+        assert_eq!(CmdrName::new("Cmdrless").display(), "Cmdrless");
+    }
+
+    #[test]
+    fn non_ascii_names_do_not_panic() {
+        assert_eq!(CmdrName::new("Ärger").key(), "ärger");
+        assert_eq!(CmdrName::new("cmd€ x").display(), "cmd€ x");
+    }
+
+    #[test]
+    fn edit_distances() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("abc", ""), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("phdeez", "phdeze"), 2);
+        assert_eq!(edit_distance("märvin", "marvin"), 1);
+    }
+
+    #[test]
+    fn closest_names_within_a_few_typos() {
+        let candidates = ["Marvin", "MARVIN THE PERPLEXED", "Marvn", "Mervin", "Arthur Dent", "marvin"];
+        assert_eq!(closest_names("marvim", candidates, 5), vec!["Marvin", "Marvn", "Mervin"]);
+        assert_eq!(closest_names("marvin the perplexd", candidates, 5), vec!["MARVIN THE PERPLEXED"]);
+        assert_eq!(closest_names("marvim", candidates, 1), vec!["Marvin"]);
+        // The input itself is not a suggestion
+        assert_eq!(closest_names("Marvin", candidates, 5), vec!["Marvn", "Mervin"]);
+        assert!(closest_names("zaphod", candidates, 5).is_empty());
+    }
+
+    #[test]
+    fn most_common_spelling_wins() {
+        let mut counter = SpellingCounter::default();
+        assert_eq!(counter.most_common(), None);
+        counter.add(&CmdrName::new("PHDEEZ"));
+        counter.add(&CmdrName::new("phDeez"));
+        counter.add(&CmdrName::new("CMDR phDeez"));
+        assert_eq!(counter.most_common(), Some("phDeez".to_string()));
+
+        let mut tie = SpellingCounter::default();
+        tie.add(&CmdrName::new("phdeez"));
+        tie.add(&CmdrName::new("PhDeez"));
+        assert_eq!(tie.most_common(), Some("PhDeez".to_string()));
+    }
+}
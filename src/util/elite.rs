@@ -0,0 +1,158 @@
+//! Names of Elite Dangerous journal identifiers, so ships and ranks are shown the same way everywhere.
+
+/// Journal symbols of all ships, SRVs and fighters a CMDR can be killed in, and their display names.
+/// The journal is not consistent about casing, so symbols are compared case-insensitively.
+const SHIPS: &[(&str, &str)] = &[
+    ("adder", "Adder"),
+    ("anaconda", "Anaconda"),
+    ("asp", "Asp Explorer"),
+    ("asp_scout", "Asp Scout"),
+    ("belugaliner", "Beluga Liner"),
+    ("cobramkiii", "Cobra Mk III"),
+    ("cobramkiv", "Cobra Mk IV"),
+    ("cobramkv", "Cobra Mk V"),
+    ("corsair", "Corsair"),
+    ("cutter", "Imperial Cutter"),
+    ("diamondback", "Diamondback Scout"),
+    ("diamondbackxl", "Diamondback Explorer"),
+    ("dolphin", "Dolphin"),
+    ("eagle", "Eagle"),
+    ("empire_courier", "Imperial Courier"),
+    ("empire_eagle", "Imperial Eagle"),
+    ("empire_trader", "Imperial Clipper"),
+    ("explorer_nx", "Caspian Explorer"),
+    ("federation_corvette", "Federal Corvette"),
+    ("federation_dropship", "Federal Dropship"),
+    ("federation_dropship_mkii", "Federal Assault Ship"),
+    ("federation_gunship", "Federal Gunship"),
+    ("ferdelance", "Fer-de-Lance"),
+    ("hauler", "Hauler"),
+    ("independant_trader", "Keelback"),
+    ("krait_light", "Krait Phantom"),
+    ("krait_mkii", "Krait Mk II"),
+    ("lakonminer", "Type-11 Prospector"),
+    ("mamba", "Mamba"),
+    ("mandalay", "Mandalay"),
+    ("orca", "Orca"),
+    ("panthermkii", "Panther Clipper Mk II"),
+    ("python", "Python"),
+    ("python_nx", "Python Mk II"),
+    ("sidewinder", "Sidewinder"),
+    ("smallcombat01_nx", "Kestrel Mk II"),
+    ("type6", "Type-6 Transporter"),
+    ("type7", "Type-7 Transporter"),
+    ("type8", "Type-8 Transporter"),
+    ("type9", "Type-9 Heavy"),
+    ("type9_military", "Type-10 Defender"),
+    ("typex", "Alliance Chieftain"),
+    ("typex_2", "Alliance Crusader"),
+    ("typex_3", "Alliance Challenger"),
+    ("viper", "Viper Mk III"),
+    ("viper_mkiv", "Viper Mk IV"),
+    ("vulture", "Vulture"),
+    // Fighters
+    ("empire_fighter", "Imperial Fighter"),
+    ("federation_fighter", "F63 Condor"),
+    ("independent_fighter", "Taipan"),
+    ("gdn_hybrid_fighter_v1", "Trident"),
+    ("gdn_hybrid_fighter_v2", "Javelin"),
+    ("gdn_hybrid_fighter_v3", "Lance"),
+    // SRVs
+    ("testbuggy", "Scarab"),
+    ("combat_multicrew_srv_01", "Scorpion"),
+];
+
+/// Combat ranks by their number in the journal. 9 to 13 are the ranks past Elite.
+const COMBAT_RANKS: &[&str] = &[
+    "Harmless",
+    "Mostly Harmless",
+    "Novice",
+    "Competent",
+    "Expert",
+    "Master",
+    "Dangerous",
+    "Deadly",
+    "Elite",
+    "Elite I",
+    "Elite II",
+    "Elite III",
+    "Elite IV",
+    "Elite V",
+];
+
+pub const UNKNOWN_SHIP: &str = "Unknown ship";
+
+/// Display name of a ship from its journal symbol, e.g. `empire_trader` is the Imperial Clipper.
+/// `None` if the ship is unknown. Symbols missing from the table, like ships released after this was written, are shown without the underscores.
+pub fn ship_name(symbol: &str) -> Option<String> {
+    let symbol = symbol.trim();
+    if symbol.is_empty() || symbol.eq_ignore_ascii_case("unknown") {
+        return None;
+    }
+    return match SHIPS.iter().find(|(x, _)| x.eq_ignore_ascii_case(symbol)) {
+        Some((_, name)) => Some(name.to_string()),
+        None => Some(symbol.replace('_', " "))
+    };
+}
+
+/// Name of a combat rank from its number in the journal. `None` if it is not a rank.
+pub fn combat_rank_name(rank: &str) -> Option<&'static str> {
+    let index = rank.trim().parse::<usize>().ok()?;
+    return COMBAT_RANKS.get(index).copied();
+}
+
+/// "Mamba (Deadly)", or whatever part of it is known.
+pub fn describe_pilot(ship: &str, rank: &str) -> String {
+    return match (ship_name(ship), combat_rank_name(rank)) {
+        (Some(ship), Some(rank)) => format!("{} ({})", ship, rank),
+        (Some(ship), None) => ship,
+        (None, Some(rank)) => format!("{} ({})", UNKNOWN_SHIP, rank),
+        (None, None) => UNKNOWN_SHIP.to_string()
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{combat_rank_name, describe_pilot, ship_name, SHIPS};
+
+    #[test]
+    fn ship_names_ignore_case() {
+        assert_eq!(ship_name("empire_trader").as_deref(), Some("Imperial Clipper"));
+        assert_eq!(ship_name("Empire_Trader").as_deref(), Some("Imperial Clipper"));
+        assert_eq!(ship_name("Federation_Corvette").as_deref(), Some("Federal Corvette"));
+        assert_eq!(ship_name(" ferdelance ").as_deref(), Some("Fer-de-Lance"));
+        assert_eq!(ship_name("TypeX_3").as_deref(), Some("Alliance Challenger"));
+    }
+
+    #[test]
+    fn unknown_ships() {
+        assert_eq!(ship_name("Unknown"), None);
+        assert_eq!(ship_name(""), None);
+        assert_eq!(ship_name("some_new_ship").as_deref(), Some("some new ship"));
+    }
+
+    #[test]
+    fn ship_table_has_no_duplicates() {
+        for (i, (symbol, _)) in SHIPS.iter().enumerate() {
+            assert_eq!(symbol.to_lowercase(), *symbol);
+            assert!(SHIPS[i + 1..].iter().all(|(x, _)| x != symbol), "{} is listed twice", symbol);
+        }
+    }
+
+    #[test]
+    fn combat_ranks() {
+        assert_eq!(combat_rank_name("0"), Some("Harmless"));
+        assert_eq!(combat_rank_name("8"), Some("Elite"));
+        assert_eq!(combat_rank_name("13"), Some("Elite V"));
+        assert_eq!(combat_rank_name("14"), None);
+        assert_eq!(combat_rank_name("-1"), None);
+    }
+
+    #[test]
+    fn pilots() {
+        assert_eq!(describe_pilot("mamba", "7"), "Mamba (Deadly)");
+        assert_eq!(describe_pilot("mamba", ""), "Mamba");
+        assert_eq!(describe_pilot("Unknown", "6"), "Unknown ship (Dangerous)");
+        assert_eq!(describe_pilot("Unknown", "Unknown"), "Unknown ship");
+    }
+}
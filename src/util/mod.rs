@@ -1 +1,4 @@
-pub mod new_username;
\ No newline at end of file
+pub mod new_username;
+pub mod cmdr_name;
+pub mod elite;
+pub mod text;
@@ -0,0 +1,53 @@
+/// Discord rejects embeds with longer field values.
+pub const FIELD_VALUE_LIMIT: usize = 1024;
+
+/// Longest CMDR or system name shown in a column of a table. Keeps a full page of every column below [FIELD_VALUE_LIMIT].
+pub const CELL_LIMIT: usize = 48;
+
+/// Shortens `value` to at most `limit` characters, marking the cut with an ellipsis.
+pub fn truncate(value: &str, limit: usize) -> String {
+    if value.chars().count() <= limit {
+        return value.to_string();
+    }
+    let mut truncated: String = value.chars().take(limit - 1).collect();
+    truncated.push('…');
+    return truncated;
+}
+
+/// Joins the lines of a column into a field value. Lines that do not fit are dropped, so every line stays next to its neighbours in the other columns.
+pub fn to_field_value(lines: &[String]) -> String {
+    let mut value = String::new();
+    for line in lines {
+        let needed = line.chars().count() + if value.is_empty() { 0 } else { 1 };
+        if value.chars().count() + needed > FIELD_VALUE_LIMIT {
+            break;
+        }
+        if !value.is_empty() {
+            value.push('\n');
+        }
+        value.push_str(line);
+    }
+    return value;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_field_value, truncate, FIELD_VALUE_LIMIT};
+
+    #[test]
+    fn truncates_long_values() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("exactly 10", 10), "exactly 10");
+        assert_eq!(truncate("MARVIN THE PERPLEXED", 10), "MARVIN TH…");
+        assert_eq!(truncate("ÄÖÜÄÖÜ", 4), "ÄÖÜ…");
+    }
+
+    #[test]
+    fn field_values_never_exceed_the_limit() {
+        let lines: Vec<String> = (0..100).map(|_| "x".repeat(50)).collect();
+        let value = to_field_value(&lines);
+        assert!(value.chars().count() <= FIELD_VALUE_LIMIT);
+        // Only whole lines are kept
+        assert!(value.split('\n').all(|x| x.len() == 50));
+    }
+}
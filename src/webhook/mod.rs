@@ -0,0 +1,180 @@
+//! Optional HTTP listener the Killboard can push new kills to, instead of waiting for the next cache sync.
+//!
+//! Kills are `POST`ed to `/webhook/kills`, either as a single object or as an array, in the same shape as
+//! `/killboard/get/kills` returns them. Kills may additionally carry the journal symbols of both ships as `killer_ship` and
+//! `victim_ship`, which the live feed then shows. The request must carry the shared secret as `Authorization: Bearer <WEBHOOK_SECRET>`.
+//!
+//! ```sh
+//! curl -X POST http://localhost:8080/webhook/kills \
+//!     -H "Authorization: Bearer $WEBHOOK_SECRET" -H "Content-Type: application/json" \
+//!     --data @mock_server/webhook_kills.json
+//! ```
+
+use axum::{Router, routing::post, extract::State, http::{HeaderMap, StatusCode, header::AUTHORIZATION}, Json, body::Bytes};
+use serde::{Deserialize, Serialize};
+
+use crate::{data::Environment, data_server::models::{_RangeResponseEntry, RangeResponseEntry}, other::live_feed::publish_new_kills, Data};
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KillPayload {
+    Single(_RangeResponseEntry),
+    Multiple(Vec<_RangeResponseEntry>)
+}
+
+#[derive(Serialize)]
+struct KillPayloadResponse {
+    received: usize,
+    new: usize
+}
+
+#[derive(Serialize, Debug)]
+struct ErrorResponse {
+    error: String
+}
+
+type HandlerResult<T> = Result<Json<T>, (StatusCode, Json<ErrorResponse>)>;
+
+fn error_response(status: StatusCode, error: String) -> (StatusCode, Json<ErrorResponse>) {
+    return (status, Json(ErrorResponse { error }));
+}
+
+/// Starts the listener if `WEBHOOK_LISTEN_ADDRESS` is set. Runs for as long as the Bot runs.
+pub fn spawn_webhook_listener(bot_data: Data) {
+    let (address, secret) = match (Environment::webhook_listen_address(), Environment::webhook_secret()) {
+        (Some(address), Some(secret)) => (address, secret),
+        _ => return
+    };
+
+    let app = Router::new()
+        .route("/webhook/kills", post(receive_kills))
+        .with_state((bot_data, secret));
+
+    tokio::spawn(async move {
+        println!("Webhook listening on {}", address);
+        if let Err(err) = axum::Server::bind(&address).serve(app.into_make_service()).await {
+            println!("[ERROR] Webhook listener stopped: {}", err);
+        }
+    });
+}
+
+async fn receive_kills(
+    State((bot_data, secret)): State<(Data, String)>,
+    headers: HeaderMap,
+    body: Bytes
+) -> HandlerResult<KillPayloadResponse> {
+    let kills = parse_kills(&headers, &secret, &body)?;
+
+    let new = bot_data.kill_cache.insert_live_kills(&kills)
+        .map_err(|x| error_response(StatusCode::INTERNAL_SERVER_ERROR, x.to_string()))?;
+
+    if new > 0 {
+        // Posting to Discord may take a while when many kills arrive, no need to keep the Killboard waiting for it.
+        tokio::spawn(async move {
+            if let Err(err) = publish_new_kills(&bot_data.kill_cache).await {
+                println!("[WARN] Failed to publish pushed kills to the live feed: {}", err);
+            }
+        });
+    }
+
+    return Ok(Json(KillPayloadResponse { received: kills.len(), new }));
+}
+
+/// Checks the secret, then reads and validates the kills of the request.
+fn parse_kills(headers: &HeaderMap, secret: &str, body: &[u8]) -> Result<Vec<RangeResponseEntry>, (StatusCode, Json<ErrorResponse>)> {
+    // The body is only parsed after the secret was checked, unauthenticated callers learn nothing about the expected format.
+    if !is_authorized(headers, secret) {
+        return Err(error_response(StatusCode::UNAUTHORIZED, "Missing or wrong shared secret".to_string()));
+    }
+
+    let payload: KillPayload = serde_json::from_slice(body)
+        .map_err(|x| error_response(StatusCode::BAD_REQUEST, format!("Body is not a kill or a list of kills: {}", x)))?;
+
+    let raw_kills = match payload {
+        KillPayload::Single(e) => vec![e],
+        KillPayload::Multiple(e) => e
+    };
+
+    let mut kills: Vec<RangeResponseEntry> = vec![];
+    for raw_kill in raw_kills {
+        if raw_kill.killer_name.trim().is_empty() || raw_kill.victim_name.trim().is_empty() {
+            return Err(error_response(StatusCode::UNPROCESSABLE_ENTITY, format!("Kill {} is missing the killer or victim name", raw_kill.id)));
+        }
+        if let Err(err) = raw_kill.parse_timestamp() {
+            return Err(error_response(StatusCode::UNPROCESSABLE_ENTITY, format!("Kill {} has an invalid timestamp: {}", raw_kill.id, err)));
+        }
+        kills.push(raw_kill.convert());
+    }
+    return Ok(kills);
+}
+
+fn is_authorized(headers: &HeaderMap, secret: &str) -> bool {
+    let provided = match headers.get(AUTHORIZATION).and_then(|x| x.to_str().ok()).and_then(|x| x.strip_prefix("Bearer ")) {
+        None => return false,
+        Some(e) => e
+    };
+    return constant_time_eq(provided.as_bytes(), secret.as_bytes());
+}
+
+/// Compares without returning early, so the time taken does not tell how much of the secret was guessed correctly.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    return a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{HeaderMap, HeaderValue, StatusCode, header::AUTHORIZATION};
+
+    use super::parse_kills;
+
+    const SECRET: &str = "s3cret";
+
+    fn headers(authorization: Option<&str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(authorization) = authorization {
+            headers.insert(AUTHORIZATION, HeaderValue::from_str(authorization).unwrap());
+        }
+        return headers;
+    }
+
+    fn status(authorization: Option<&str>, body: &str) -> StatusCode {
+        return match parse_kills(&headers(authorization), SECRET, body.as_bytes()) {
+            Ok(_) => StatusCode::OK,
+            Err((status, _)) => status
+        };
+    }
+
+    #[test]
+    fn rejects_wrong_secrets_before_looking_at_the_body() {
+        assert_eq!(status(None, "not json"), StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some("Bearer wrong"), "not json"), StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some("Bearer s3cret2"), "not json"), StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some("s3cret"), "not json"), StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some("Bearer s3cret"), "not json"), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn accepts_a_single_kill_or_a_list() {
+        let kill = r#"{"id": 1, "timestamp": "2023-12-11 21:02:41", "killer_name": "Alpha", "victim_name": "Bravo", "location": "Deciat", "killer_ship": "mamba"}"#;
+        let kills = parse_kills(&headers(Some("Bearer s3cret")), SECRET, kill.as_bytes()).unwrap();
+        assert_eq!(kills.len(), 1);
+        assert_eq!(kills[0].killer_ship.as_deref(), Some("mamba"));
+
+        let list = format!("[{}, {}]", kill, kill.replace("\"id\": 1", "\"id\": 2"));
+        let kills = parse_kills(&headers(Some("Bearer s3cret")), SECRET, list.as_bytes()).unwrap();
+        assert_eq!(kills.iter().map(|x| x.id).collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn rejects_incomplete_kills() {
+        let missing_victim = r#"{"id": 1, "timestamp": "2023-12-11 21:02:41", "killer_name": "Alpha", "victim_name": " ", "location": "Deciat"}"#;
+        assert_eq!(status(Some("Bearer s3cret"), missing_victim), StatusCode::UNPROCESSABLE_ENTITY);
+        let bad_timestamp = r#"{"id": 1, "timestamp": "2023-12-11T21:02:41Z", "killer_name": "Alpha", "victim_name": "Bravo", "location": "Deciat"}"#;
+        assert_eq!(status(Some("Bearer s3cret"), bad_timestamp), StatusCode::UNPROCESSABLE_ENTITY);
+        let missing_field = r#"{"id": 1, "killer_name": "Alpha", "victim_name": "Bravo", "location": "Deciat"}"#;
+        assert_eq!(status(Some("Bearer s3cret"), missing_field), StatusCode::BAD_REQUEST);
+    }
+}